@@ -0,0 +1,75 @@
+use serde::de::DeserializeOwned;
+
+use super::error::{Error, REDACTED};
+
+pub const DEFAULT_BASE_URL: &str = "https://api.openweathermap.org";
+
+#[derive(Clone)]
+pub struct OpenWeatherClient {
+    client: reqwest::Client,
+    key: String,
+    base_url: String,
+}
+
+// The key is left out, so that the client can be logged
+impl std::fmt::Debug for OpenWeatherClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpenWeatherClient")
+            .field("key", &REDACTED)
+            .field("base_url", &self.base_url)
+            .finish()
+    }
+}
+
+impl OpenWeatherClient {
+    pub fn new(key: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            key: key.to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.to_string();
+        self
+    }
+
+    // Sends a GET request to `path` (e.g. "/data/2.5/weather") with the API key
+    // appended to `query`, and deserializes the response body. Errors never
+    // contain the key
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, Error> {
+        self.send(path, query)
+            .await
+            .map_err(|e| e.redact(&self.key))
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, Error> {
+        let url = format!("{}{}", self.base_url.trim_end_matches('/'), path);
+
+        let response = self
+            .client
+            .get(url)
+            .query(query)
+            .query(&[("appid", &self.key)])
+            .send()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            return Err(Error::from_response(status, &body));
+        }
+
+        Ok(serde_json::from_str(&body)?)
+    }
+}
@@ -0,0 +1,158 @@
+use serde::Deserialize;
+
+// Shown in place of the API key
+pub const REDACTED: &str = "***";
+
+// Replaces the value of the `appid` query parameter, which holds the API key
+pub fn redact_url(url: &mut reqwest::Url) {
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| match name.as_ref() {
+            "appid" => (name.into_owned(), REDACTED.to_string()),
+            _ => (name.into_owned(), value.into_owned()),
+        })
+        .collect();
+
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+}
+
+// Body returned by OpenWeather alongside a non-2xx status, e.g.
+// {"cod": 401, "message": "Invalid API key. Please see ..."}
+// Depending on the endpoint, cod is either a number or a string
+#[derive(Deserialize, Debug)]
+pub struct ApiError {
+    pub cod: Option<serde_json::Value>,
+    pub message: Option<String>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("No API key found! Pass one with --key, set OWM_API_KEY or set `key`, `key_command` or `key_file` in the configuration file")]
+    MissingKey,
+    #[error("Unknown API: {0} (expected one of: current, forecast, astro, air, air-forecast, air-history, reverse)")]
+    UnknownApi(String),
+    #[error("Invalid template: {0}")]
+    Template(String),
+    #[error("Invalid time format: {0}")]
+    TimeFormat(String),
+    #[error("Invalid date: {0} (expected YYYY-MM-DD or YYYY-MM-DD HH:MM)")]
+    InvalidDate(String),
+    #[error("Could not resolve a location, provide --lat/--lon, --city/--state/--country or --zip/--country")]
+    LocationNotFound,
+    #[error("Several places match, pass --pick N to choose one:\n{0}")]
+    AmbiguousLocation(String),
+    #[error("Could not update the configuration file {0}")]
+    Config(String),
+    #[error("Could not read the configuration file {0}")]
+    ConfigFile(String),
+    #[error("Invalid environment variable {0}")]
+    InvalidEnv(String),
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Aborted: {0}")]
+    Aborted(String),
+    #[error("Could not get the API key: {0}")]
+    KeySource(String),
+    #[error("Keyring error: {0}")]
+    Keyring(String),
+    #[error("No location named {0} in the configuration file")]
+    UnknownLocation(String),
+    #[error("Invalid location: {0}")]
+    InvalidLocation(String),
+    #[error("Could not read the locations file {0}")]
+    LocationsFile(String),
+    #[error("{0} does not support several locations")]
+    BatchUnsupported(String),
+    #[error("{failed} of {total} locations failed")]
+    BatchFailed { failed: usize, total: usize },
+    #[error("The API key was rejected: {0}")]
+    Unauthorized(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Rate limit exceeded: {0}")]
+    RateLimited(String),
+    #[error("Request failed with status code {status}: {message}")]
+    Api { status: u16, message: String },
+    #[error("Network error: {0}")]
+    Network(#[from] reqwest::Error),
+    #[error("Failed to deserialize response body: {0}")]
+    Deserialize(#[from] serde_json::Error),
+}
+
+impl Error {
+    // Maps an unsuccessful response to the matching variant, using the message
+    // from the error body when OpenWeather provides one
+    pub fn from_response(status: reqwest::StatusCode, body: &str) -> Self {
+        let message = match serde_json::from_str::<ApiError>(body) {
+            Ok(ApiError {
+                message: Some(message),
+                ..
+            }) => message,
+            _ => status
+                .canonical_reason()
+                .unwrap_or("Unknown error")
+                .to_string(),
+        };
+
+        match status.as_u16() {
+            401 => Self::Unauthorized(message),
+            404 => Self::NotFound(message),
+            429 => Self::RateLimited(message),
+            status => Self::Api { status, message },
+        }
+    }
+
+    // Masks the API key wherever it could surface: in the request URL that
+    // network errors carry, and in messages the API sends back
+    pub fn redact(self, key: &str) -> Self {
+        if key.is_empty() {
+            return self;
+        }
+        let mask = |message: String| message.replace(key, REDACTED);
+
+        match self {
+            Self::Network(mut e) => {
+                if let Some(url) = e.url_mut() {
+                    redact_url(url);
+                }
+                Self::Network(e)
+            }
+            Self::Unauthorized(message) => Self::Unauthorized(mask(message)),
+            Self::NotFound(message) => Self::NotFound(mask(message)),
+            Self::RateLimited(message) => Self::RateLimited(mask(message)),
+            Self::Api { status, message } => Self::Api {
+                status,
+                message: mask(message),
+            },
+            e => e,
+        }
+    }
+
+    // Exit codes are part of the CLI's interface, see the README before changing them
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Api { .. } | Self::Deserialize(_) | Self::BatchFailed { .. } => 1,
+            Self::MissingKey
+            | Self::UnknownApi(_)
+            | Self::Template(_)
+            | Self::TimeFormat(_)
+            | Self::InvalidDate(_)
+            | Self::Config(_)
+            | Self::ConfigFile(_)
+            | Self::InvalidEnv(_)
+            | Self::InvalidConfig(_)
+            | Self::Aborted(_)
+            | Self::KeySource(_)
+            | Self::Keyring(_)
+            | Self::UnknownLocation(_)
+            | Self::InvalidLocation(_)
+            | Self::LocationsFile(_)
+            | Self::BatchUnsupported(_) => 2,
+            Self::Unauthorized(_) => 3,
+            Self::NotFound(_) | Self::LocationNotFound => 4,
+            Self::RateLimited(_) => 5,
+            Self::Network(_) => 6,
+            Self::AmbiguousLocation(_) => 7,
+        }
+    }
+}
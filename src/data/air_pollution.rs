@@ -0,0 +1,330 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::{client::OpenWeatherClient, error::Error},
+    data::{
+        aqi::{caqi, caqi_category, epa_aqi, epa_category, owm_label},
+        convert::nautical_offset,
+        record::{Field, Record, Value},
+    },
+    options::{args::Args, config::Config, format::Summary, settings::Settings},
+};
+
+use super::{
+    data::{Data, Dataset},
+    geocoding::Geocoding,
+};
+
+// Every print option, in the order used when no --print is given
+pub const OPTIONS: &[&str] = &[
+    "aqi",
+    "aqi_label",
+    "epa_aqi",
+    "epa_category",
+    "caqi",
+    "caqi_category",
+    "co",
+    "no",
+    "no2",
+    "o3",
+    "so2",
+    "pm2_5",
+    "pm10",
+    "nh3",
+];
+
+pub fn label(opt: &str) -> Option<&'static str> {
+    match opt {
+        "aqi" => Some("Air Quality Index"),
+        "aqi_label" => Some("Air Quality"),
+        "epa_aqi" => Some("US EPA AQI"),
+        "epa_category" => Some("US EPA Category"),
+        "caqi" => Some("European CAQI"),
+        "caqi_category" => Some("CAQI Category"),
+        "co" => Some("CO"),
+        "no" => Some("NO"),
+        "no2" => Some("NO2"),
+        "o3" => Some("O3"),
+        "so2" => Some("SO2"),
+        "pm2_5" => Some("PM2.5"),
+        "pm10" => Some("PM10"),
+        "nh3" => Some("NH3"),
+        _ => None,
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Coord {
+    pub lon: Option<f64>,
+    pub lat: Option<f64>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Main {
+    pub aqi: Option<i32>,
+}
+
+// Concentrations in μg/m³
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Components {
+    pub co: Option<f32>,
+    pub no: Option<f32>,
+    pub no2: Option<f32>,
+    pub o3: Option<f32>,
+    pub so2: Option<f32>,
+    pub pm2_5: Option<f32>,
+    pub pm10: Option<f32>,
+    pub nh3: Option<f32>,
+}
+
+impl Components {
+    fn all(&self) -> [(&'static str, Option<f32>); 8] {
+        [
+            ("co", self.co),
+            ("no", self.no),
+            ("no2", self.no2),
+            ("o3", self.o3),
+            ("so2", self.so2),
+            ("pm2_5", self.pm2_5),
+            ("pm10", self.pm10),
+            ("nh3", self.nh3),
+        ]
+    }
+
+    fn get(&self, pollutant: &str) -> Option<f32> {
+        self.all()
+            .iter()
+            .find(|(name, _)| *name == pollutant)
+            .and_then(|(_, concentration)| *concentration)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Entry {
+    pub dt: Option<i64>,
+    pub main: Option<Main>,
+    pub components: Option<Components>,
+}
+
+impl Entry {
+    fn value(&self, opt: &str) -> Option<Value> {
+        let aqi = self.main.as_ref().and_then(|main| main.aqi);
+        let components = self.components.as_ref();
+        let concentrations = components.map(|c| c.all()).unwrap_or_default();
+
+        match opt {
+            "aqi" => aqi.map(|aqi| Value::number(aqi, "")),
+            "aqi_label" => aqi.and_then(owm_label).map(Value::text),
+            "epa_aqi" => epa_aqi(&concentrations).map(|aqi| Value::number(aqi, "")),
+            "epa_category" => epa_aqi(&concentrations).map(|aqi| Value::text(epa_category(aqi))),
+            "caqi" => caqi(&concentrations).map(|caqi| Value::number(caqi, "")),
+            "caqi_category" => caqi(&concentrations).map(|caqi| Value::text(caqi_category(caqi))),
+            opt => components
+                .and_then(|c| c.get(opt))
+                .map(|c| Value::number(format!("{:.2}", c), "μg/m³")),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct AirPollution {
+    pub coord: Option<Coord>,
+    pub list: Option<Vec<Entry>>,
+    // Whether this is the current reading rather than a forecast or history
+    #[serde(skip)]
+    pub current: bool,
+}
+
+impl AirPollution {
+    // --api air gets the current reading, air-forecast the hourly forecast for
+    // the next days and air-history hourly readings between --start and --end,
+    // which default to the last 24 hours
+    pub async fn get(
+        client: &OpenWeatherClient,
+        args: &Args,
+        config: &Config,
+        api: &str,
+    ) -> Result<Data, Error> {
+        let (lat, lon) = Geocoding::locate(client, args, config).await?;
+
+        let mut query = vec![("lat", lat.to_string()), ("lon", lon.to_string())];
+        let path = match api {
+            "air-forecast" => "/data/2.5/air_pollution/forecast",
+            "air-history" => {
+                let settings = Settings::new(args, config);
+                let offset = Some(nautical_offset(lon));
+                let end = match &args.end {
+                    Some(end) => settings.timestamp(end, offset)?,
+                    None => Utc::now().timestamp(),
+                };
+                let start = match &args.start {
+                    Some(start) => settings.timestamp(start, offset)?,
+                    None => end - 86400,
+                };
+
+                query.push(("start", start.to_string()));
+                query.push(("end", end.to_string()));
+                "/data/2.5/air_pollution/history"
+            }
+            _ => "/data/2.5/air_pollution",
+        };
+
+        let mut data: AirPollution = client.get(path, &query).await?;
+        data.current = api == "air";
+
+        Ok(Data::AirPollution(data))
+    }
+
+    // The API does not report the location's offset, so times use its
+    // nautical time zone
+    fn timezone(&self) -> Option<i32> {
+        self.coord
+            .as_ref()
+            .and_then(|coord| coord.lon)
+            .map(nautical_offset)
+    }
+
+    fn list(&self) -> Vec<Entry> {
+        self.list.clone().unwrap_or_default()
+    }
+
+    // Looks up a single print option of an entry, or None if the option is
+    // not known
+    fn field(&self, opt: &str, entry: &Entry) -> Option<Field> {
+        Some(Field::new(opt, label(opt)?, entry.value(opt).into()))
+    }
+}
+
+impl Dataset for AirPollution {
+    fn single(&self) -> bool {
+        self.current
+    }
+
+    // One record per reading. Forecasts and history lead each with the time it
+    // covers
+    fn records(&self, opts: &[&str], args: &Args, config: &Config) -> Vec<Record> {
+        let settings = Settings::new(args, config);
+        let opts = match opts.is_empty() {
+            true => OPTIONS,
+            false => opts,
+        };
+
+        self.list()
+            .iter()
+            .map(|entry| {
+                let mut record = Record::default();
+                if !self.current {
+                    record.push(Field::new(
+                        "time",
+                        "Time",
+                        entry
+                            .dt
+                            .map(|dt| Value::text(settings.datetime(dt, self.timezone())))
+                            .into(),
+                    ));
+                }
+                for opt in opts {
+                    record.push(
+                        self.field(opt, entry)
+                            .unwrap_or_else(|| Field::new(opt, opt, Value::Null)),
+                    );
+                }
+                record
+            })
+            .collect()
+    }
+
+    // Every print option plus the coordinates, for use in templates
+    fn template_records(&self, args: &Args, config: &Config) -> Vec<Record> {
+        let coord = self.coord.clone();
+        let (lat, lon) = (
+            coord.as_ref().and_then(|c| c.lat),
+            coord.and_then(|c| c.lon),
+        );
+
+        self.records(&[], args, config)
+            .into_iter()
+            .map(|mut record| {
+                record.push(Field::new(
+                    "lat",
+                    "Latitude",
+                    lat.map(|lat| Value::number(lat, "")).into(),
+                ));
+                record.push(Field::new(
+                    "lon",
+                    "Longitude",
+                    lon.map(|lon| Value::number(lon, "")).into(),
+                ));
+                record
+            })
+            .collect()
+    }
+
+    fn print(&self, opt: &str, args: &Args, config: &Config) {
+        if label(opt).is_none() {
+            println!("No data to print for option {}", opt);
+            return;
+        }
+
+        for record in self.records(&[opt], args, config) {
+            match (self.current, record.fields.as_slice()) {
+                (true, [field]) if !field.value.is_null() => {
+                    println!("{}", field.text(args.verbose))
+                }
+                (false, [when, field]) if !field.value.is_null() => {
+                    println!("[{}] {}", when.value, field.text(args.verbose))
+                }
+                _ => println!("No data to print for option {}", opt),
+            }
+        }
+    }
+
+    // Report of each reading: the indices, followed by the concentrations for
+    // the current reading. Parts missing from the response are left out
+    fn summary(&self, summary: Summary, args: &Args, config: &Config) -> String {
+        let settings = Settings::new(args, config);
+        let mut lines = vec![];
+
+        for entry in self.list() {
+            let value = |opt: &str| entry.value(opt).unwrap_or(Value::Null);
+
+            let mut indices = vec![];
+            for (label, index, category) in [
+                ("", "aqi", "aqi_label"),
+                ("US EPA AQI ", "epa_aqi", "epa_category"),
+                ("CAQI ", "caqi", "caqi_category"),
+            ] {
+                let (index, category) = (value(index), value(category));
+                match (index.is_null(), category.is_null()) {
+                    (false, false) if label.is_empty() => {
+                        indices.push(format!("{} ({})", category, index))
+                    }
+                    (false, false) => indices.push(format!("{}{} ({})", label, index, category)),
+                    (false, true) => indices.push(format!("{}{}", label, index)),
+                    _ => {}
+                }
+            }
+
+            let when = match (self.current, entry.dt) {
+                (false, Some(dt)) => format!("[{}] ", settings.datetime(dt, self.timezone())),
+                _ => String::new(),
+            };
+
+            if summary == Summary::Short || !self.current {
+                lines.push(format!("{}Air quality: {}", when, indices.join(", ")));
+                continue;
+            }
+
+            lines.push(format!("Air quality: {}", indices.join(", ")));
+            for opt in ["pm2_5", "pm10", "o3", "no2", "so2", "co", "no", "nh3"] {
+                let value = value(opt);
+                if !value.is_null() {
+                    lines.push(format!("  {}: {}", label(opt).unwrap_or(opt), value));
+                }
+            }
+        }
+
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+}
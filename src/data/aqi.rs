@@ -0,0 +1,180 @@
+// Air quality indices computed from pollutant concentrations in μg/m³, as
+// returned by the air pollution API
+
+// (concentration low, concentration high, index low, index high)
+type Breakpoints = &'static [(f32, f32, f32, f32)];
+
+// US EPA breakpoints, in the units the EPA uses for each pollutant. PM2.5 uses
+// the 2024 revision. Ozone uses the 8 hour breakpoints up to 200 ppb, where
+// they end, and the 1 hour ones from 405 ppb. The 1 hour row for 205-404 ppb
+// spans the same 201-300 category the 8 hour table ends in, so the gap
+// between them is held at 300 rather than dropping back to 201
+const EPA_PM2_5: Breakpoints = &[
+    (0.0, 9.0, 0.0, 50.0),
+    (9.1, 35.4, 51.0, 100.0),
+    (35.5, 55.4, 101.0, 150.0),
+    (55.5, 125.4, 151.0, 200.0),
+    (125.5, 225.4, 201.0, 300.0),
+    (225.5, 325.4, 301.0, 500.0),
+];
+const EPA_PM10: Breakpoints = &[
+    (0.0, 54.0, 0.0, 50.0),
+    (55.0, 154.0, 51.0, 100.0),
+    (155.0, 254.0, 101.0, 150.0),
+    (255.0, 354.0, 151.0, 200.0),
+    (355.0, 424.0, 201.0, 300.0),
+    (425.0, 604.0, 301.0, 500.0),
+];
+const EPA_O3: Breakpoints = &[
+    (0.0, 54.0, 0.0, 50.0),
+    (55.0, 70.0, 51.0, 100.0),
+    (71.0, 85.0, 101.0, 150.0),
+    (86.0, 105.0, 151.0, 200.0),
+    (106.0, 200.0, 201.0, 300.0),
+    (201.0, 404.0, 300.0, 300.0),
+    (405.0, 504.0, 301.0, 400.0),
+    (505.0, 604.0, 401.0, 500.0),
+];
+const EPA_NO2: Breakpoints = &[
+    (0.0, 53.0, 0.0, 50.0),
+    (54.0, 100.0, 51.0, 100.0),
+    (101.0, 360.0, 101.0, 150.0),
+    (361.0, 649.0, 151.0, 200.0),
+    (650.0, 1249.0, 201.0, 300.0),
+    (1250.0, 2049.0, 301.0, 500.0),
+];
+const EPA_SO2: Breakpoints = &[
+    (0.0, 35.0, 0.0, 50.0),
+    (36.0, 75.0, 51.0, 100.0),
+    (76.0, 185.0, 101.0, 150.0),
+    (186.0, 304.0, 151.0, 200.0),
+    (305.0, 604.0, 201.0, 300.0),
+    (605.0, 1004.0, 301.0, 500.0),
+];
+const EPA_CO: Breakpoints = &[
+    (0.0, 4.4, 0.0, 50.0),
+    (4.5, 9.4, 51.0, 100.0),
+    (9.5, 12.4, 101.0, 150.0),
+    (12.5, 15.4, 151.0, 200.0),
+    (15.5, 30.4, 201.0, 300.0),
+    (30.5, 50.4, 301.0, 500.0),
+];
+
+// Hourly CAQI grid for background stations, in μg/m³. Each pollutant maps the
+// concentrations at index 0, 25, 50, 75 and 100
+const CAQI_GRID: &[(&str, [f32; 5])] = &[
+    ("no2", [0.0, 50.0, 100.0, 200.0, 400.0]),
+    ("pm10", [0.0, 25.0, 50.0, 90.0, 180.0]),
+    ("o3", [0.0, 60.0, 120.0, 180.0, 240.0]),
+    ("pm2_5", [0.0, 15.0, 30.0, 55.0, 110.0]),
+    ("co", [0.0, 5000.0, 7500.0, 10000.0, 20000.0]),
+    ("so2", [0.0, 50.0, 100.0, 350.0, 500.0]),
+];
+
+// Molar volume at 25°C and 1 atm, for converting μg/m³ to parts per billion
+const MOLAR_VOLUME: f32 = 24.45;
+
+fn ppb(concentration: f32, molar_mass: f32) -> f32 {
+    concentration * MOLAR_VOLUME / molar_mass
+}
+
+// Truncates to the given number of decimals, as the EPA does before looking
+// up a breakpoint
+fn truncate(value: f32, decimals: i32) -> f32 {
+    let factor = 10f32.powi(decimals);
+    (value * factor + 1e-4).floor() / factor
+}
+
+fn interpolate(concentration: f32, breakpoints: Breakpoints) -> f32 {
+    for &(c_low, c_high, i_low, i_high) in breakpoints {
+        if concentration <= c_high {
+            return i_low + (i_high - i_low) * (concentration - c_low) / (c_high - c_low);
+        }
+    }
+
+    // Beyond the index
+    500.0
+}
+
+// US EPA sub-index of a single pollutant, or None for pollutants the EPA
+// index does not cover
+pub fn epa_sub_index(pollutant: &str, concentration: f32) -> Option<f32> {
+    let (concentration, breakpoints) = match pollutant {
+        "pm2_5" => (truncate(concentration, 1), EPA_PM2_5),
+        "pm10" => (truncate(concentration, 0), EPA_PM10),
+        "o3" => (truncate(ppb(concentration, 48.00), 0), EPA_O3),
+        "no2" => (truncate(ppb(concentration, 46.01), 0), EPA_NO2),
+        "so2" => (truncate(ppb(concentration, 64.07), 0), EPA_SO2),
+        "co" => (truncate(ppb(concentration, 28.01) / 1000.0, 1), EPA_CO),
+        _ => return None,
+    };
+
+    Some(interpolate(concentration.max(0.0), breakpoints).round())
+}
+
+pub fn epa_category(aqi: f32) -> &'static str {
+    match aqi {
+        aqi if aqi <= 50.0 => "Good",
+        aqi if aqi <= 100.0 => "Moderate",
+        aqi if aqi <= 150.0 => "Unhealthy for Sensitive Groups",
+        aqi if aqi <= 200.0 => "Unhealthy",
+        aqi if aqi <= 300.0 => "Very Unhealthy",
+        _ => "Hazardous",
+    }
+}
+
+// European Common Air Quality Index sub-index of a single pollutant. Above
+// the grid, the index keeps rising at the rate of its last band
+pub fn caqi_sub_index(pollutant: &str, concentration: f32) -> Option<f32> {
+    let (_, grid) = CAQI_GRID.iter().find(|(name, _)| *name == pollutant)?;
+    let concentration = concentration.max(0.0);
+
+    let band = (1..grid.len())
+        .find(|&band| concentration <= grid[band])
+        .unwrap_or(grid.len() - 1);
+    let (c_low, c_high) = (grid[band - 1], grid[band]);
+    let i_low = 25.0 * (band - 1) as f32;
+
+    Some((i_low + 25.0 * (concentration - c_low) / (c_high - c_low)).round())
+}
+
+pub fn caqi_category(caqi: f32) -> &'static str {
+    match caqi {
+        caqi if caqi < 25.0 => "Very Low",
+        caqi if caqi < 50.0 => "Low",
+        caqi if caqi < 75.0 => "Medium",
+        caqi if caqi <= 100.0 => "High",
+        _ => "Very High",
+    }
+}
+
+// Overall indices are the highest sub-index of the pollutants available
+pub fn epa_aqi(concentrations: &[(&str, Option<f32>)]) -> Option<f32> {
+    highest(concentrations, epa_sub_index)
+}
+
+pub fn caqi(concentrations: &[(&str, Option<f32>)]) -> Option<f32> {
+    highest(concentrations, caqi_sub_index)
+}
+
+fn highest(
+    concentrations: &[(&str, Option<f32>)],
+    sub_index: fn(&str, f32) -> Option<f32>,
+) -> Option<f32> {
+    concentrations
+        .iter()
+        .filter_map(|(pollutant, concentration)| sub_index(pollutant, (*concentration)?))
+        .reduce(f32::max)
+}
+
+// OpenWeather's own 1-5 index
+pub fn owm_label(aqi: i32) -> Option<&'static str> {
+    match aqi {
+        1 => Some("Good"),
+        2 => Some("Fair"),
+        3 => Some("Moderate"),
+        4 => Some("Poor"),
+        5 => Some("Very Poor"),
+        _ => None,
+    }
+}
@@ -0,0 +1,442 @@
+use std::f64::consts::PI;
+
+use chrono::{NaiveDate, NaiveTime, Utc};
+
+use crate::{
+    client::{client::OpenWeatherClient, error::Error},
+    data::{
+        convert::{nautical_offset, to_duration},
+        record::{Field, Record, Value},
+    },
+    options::{
+        args::Args,
+        config::Config,
+        format::{Summary, Tz},
+        settings::Settings,
+    },
+};
+
+use super::{
+    data::{Data, Dataset},
+    geocoding::Geocoding,
+};
+
+// Solar elevations, in degrees, that the events of the day are defined by.
+// Sunrise and sunset allow for refraction and the radius of the sun
+const SUNRISE: f64 = -0.833;
+const CIVIL: f64 = -6.0;
+const NAUTICAL: f64 = -12.0;
+const ASTRONOMICAL: f64 = -18.0;
+const GOLDEN_HOUR: f64 = 6.0;
+const BLUE_HOUR: f64 = -4.0;
+
+// Mean length of a lunar month in days, and a new moon to count from
+// (2000-01-06 18:14 UTC)
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+const NEW_MOON: i64 = 947_182_440;
+
+fn julian_century(timestamp: f64) -> f64 {
+    (timestamp / 86400.0 + 2_440_587.5 - 2_451_545.0) / 36525.0
+}
+
+// Declination (radians) and equation of time (minutes) of the sun, following
+// the NOAA solar calculator
+fn sun(timestamp: f64) -> (f64, f64) {
+    let t = julian_century(timestamp);
+
+    let mean_longitude = (280.46646 + t * (36000.76983 + t * 0.0003032)).rem_euclid(360.0);
+    let mean_anomaly = 357.52911 + t * (35999.05029 - 0.0001537 * t);
+    let eccentricity = 0.016708634 - t * (0.000042037 + 0.0000001267 * t);
+
+    let m = mean_anomaly.to_radians();
+    let center = m.sin() * (1.914602 - t * (0.004817 + 0.000014 * t))
+        + (2.0 * m).sin() * (0.019993 - 0.000101 * t)
+        + (3.0 * m).sin() * 0.000289;
+
+    let omega = (125.04 - 1934.136 * t).to_radians();
+    let longitude = (mean_longitude + center - 0.00569 - 0.00478 * omega.sin()).to_radians();
+
+    let obliquity =
+        23.0 + (26.0 + (21.448 - t * (46.815 + t * (0.00059 - t * 0.001813))) / 60.0) / 60.0;
+    let obliquity = (obliquity + 0.00256 * omega.cos()).to_radians();
+
+    let declination = (obliquity.sin() * longitude.sin()).asin();
+
+    let y = (obliquity / 2.0).tan().powi(2);
+    let l = mean_longitude.to_radians();
+    let e = eccentricity;
+    let equation_of_time = 4.0
+        * (y * (2.0 * l).sin() - 2.0 * e * m.sin() + 4.0 * e * y * m.sin() * (2.0 * l).cos()
+            - 0.5 * y * y * (4.0 * l).sin()
+            - 1.25 * e * e * (2.0 * m).sin())
+        .to_degrees();
+
+    (declination, equation_of_time)
+}
+
+// Elevation and azimuth (clockwise from north) of the sun in degrees, without
+// allowing for refraction
+pub fn solar_position(timestamp: i64, lat: f64, lon: f64) -> (f64, f64) {
+    let (declination, equation_of_time) = sun(timestamp as f64);
+
+    let minutes = timestamp.rem_euclid(86400) as f64 / 60.0;
+    let solar_time = (minutes + equation_of_time + 4.0 * lon).rem_euclid(1440.0);
+    let hour_angle = (solar_time / 4.0 - 180.0).to_radians();
+
+    let lat = lat.to_radians();
+    let zenith = (lat.sin() * declination.sin() + lat.cos() * declination.cos() * hour_angle.cos())
+        .clamp(-1.0, 1.0)
+        .acos();
+
+    let azimuth = hour_angle
+        .sin()
+        .atan2(hour_angle.cos() * lat.sin() - declination.tan() * lat.cos());
+
+    (
+        90.0 - zenith.to_degrees(),
+        (azimuth.to_degrees() + 180.0).rem_euclid(360.0),
+    )
+}
+
+// Solar noon of the given date, which is the same at every longitude as a
+// calendar date in the location's own zone
+pub fn solar_noon(date: NaiveDate, lon: f64) -> i64 {
+    let midnight = date.and_time(NaiveTime::MIN).and_utc().timestamp() as f64;
+
+    // Refined once, using the equation of time at the first estimate
+    let mut noon = midnight + 43200.0 - lon * 240.0;
+    for _ in 0..2 {
+        let (_, equation_of_time) = sun(noon);
+        noon = midnight + (720.0 - 4.0 * lon - equation_of_time) * 60.0;
+    }
+
+    noon.round() as i64
+}
+
+// When the sun crosses `elevation` on the given date, in the morning when
+// `rising`, or None if it stays above or below it all day
+pub fn crossing(date: NaiveDate, lat: f64, lon: f64, elevation: f64, rising: bool) -> Option<i64> {
+    let noon = solar_noon(date, lon) as f64;
+    let sign = if rising { -1.0 } else { 1.0 };
+
+    let mut time = noon;
+    for _ in 0..3 {
+        let (declination, _) = sun(time);
+        let lat = lat.to_radians();
+        let cos_hour_angle = (elevation.to_radians().sin() - lat.sin() * declination.sin())
+            / (lat.cos() * declination.cos());
+
+        if !(-1.0..=1.0).contains(&cos_hour_angle) {
+            return None;
+        }
+
+        // Minutes either side of solar noon, at 4 minutes per degree
+        time = noon + sign * cos_hour_angle.acos().to_degrees() * 4.0 * 60.0;
+    }
+
+    Some(time.round() as i64)
+}
+
+// Days since the last new moon
+pub fn moon_age(timestamp: i64) -> f64 {
+    ((timestamp - NEW_MOON) as f64 / 86400.0).rem_euclid(SYNODIC_MONTH)
+}
+
+// Illuminated fraction of the moon, from 0 to 1
+pub fn moon_illumination(timestamp: i64) -> f64 {
+    (1.0 - (2.0 * PI * moon_age(timestamp) / SYNODIC_MONTH).cos()) / 2.0
+}
+
+// The named phases around new, first quarter, full and last quarter moon
+// span a day either side of them
+pub fn moon_phase(timestamp: i64) -> &'static str {
+    let age = moon_age(timestamp);
+
+    match age {
+        age if !(1.0..SYNODIC_MONTH - 1.0).contains(&age) => "New Moon",
+        age if age < SYNODIC_MONTH / 4.0 - 1.0 => "Waxing Crescent",
+        age if age < SYNODIC_MONTH / 4.0 + 1.0 => "First Quarter",
+        age if age < SYNODIC_MONTH / 2.0 - 1.0 => "Waxing Gibbous",
+        age if age < SYNODIC_MONTH / 2.0 + 1.0 => "Full Moon",
+        age if age < SYNODIC_MONTH * 3.0 / 4.0 - 1.0 => "Waning Gibbous",
+        age if age < SYNODIC_MONTH * 3.0 / 4.0 + 1.0 => "Last Quarter",
+        _ => "Waning Crescent",
+    }
+}
+
+// Every print option, in the order used when no --print is given
+pub const OPTIONS: &[&str] = &[
+    "date",
+    "sunrise",
+    "sunset",
+    "solar_noon",
+    "day_length",
+    "civil_dawn",
+    "civil_dusk",
+    "nautical_dawn",
+    "nautical_dusk",
+    "astronomical_dawn",
+    "astronomical_dusk",
+    "twilight",
+    "golden_hour",
+    "blue_hour",
+    "solar_elevation",
+    "solar_azimuth",
+    "moon_phase",
+    "moon_illumination",
+    "moon_age",
+];
+
+pub fn label(opt: &str) -> Option<&'static str> {
+    match opt {
+        "date" => Some("Date"),
+        "sunrise" => Some("Sunrise"),
+        "sunset" => Some("Sunset"),
+        "solar_noon" => Some("Solar Noon"),
+        "day_length" => Some("Day Length"),
+        "civil_dawn" => Some("Civil Dawn"),
+        "civil_dusk" => Some("Civil Dusk"),
+        "nautical_dawn" => Some("Nautical Dawn"),
+        "nautical_dusk" => Some("Nautical Dusk"),
+        "astronomical_dawn" => Some("Astronomical Dawn"),
+        "astronomical_dusk" => Some("Astronomical Dusk"),
+        "twilight" => Some("Civil Twilight"),
+        "golden_hour" => Some("Golden Hour"),
+        "blue_hour" => Some("Blue Hour"),
+        "solar_elevation" => Some("Solar Elevation"),
+        "solar_azimuth" => Some("Solar Azimuth"),
+        "moon_phase" => Some("Moon Phase"),
+        "moon_illumination" => Some("Moon Illumination"),
+        "moon_age" => Some("Moon Age"),
+        _ => None,
+    }
+}
+
+// Sun and moon for a location at an instant, computed without the API
+#[derive(Clone, Debug)]
+pub struct Astro {
+    pub lat: f64,
+    pub lon: f64,
+    pub timestamp: i64,
+    // UTC offset of the location in seconds, when known
+    pub timezone: Option<i32>,
+}
+
+impl Astro {
+    pub async fn get(
+        client: &OpenWeatherClient,
+        args: &Args,
+        config: &Config,
+    ) -> Result<Data, Error> {
+        let (lat, lon) = Geocoding::locate(client, args, config).await?;
+
+        // Without an API response the location's offset is unknown, so its
+        // nautical time zone stands in for it
+        let timezone = Some(nautical_offset(lon));
+
+        let timestamp = match &args.date {
+            Some(date) => Settings::new(args, config).timestamp(date, timezone)?,
+            None => Utc::now().timestamp(),
+        };
+
+        Ok(Data::Astro(Astro {
+            lat,
+            lon,
+            timestamp,
+            timezone,
+        }))
+    }
+
+    // The calendar date the instant falls on, in the zone selected with --tz
+    fn date(&self, settings: &Settings) -> NaiveDate {
+        NaiveDate::parse_from_str(&settings.date(self.timestamp, self.timezone), "%Y-%m-%d")
+            .expect("Could not parse formatted date!")
+    }
+
+    fn crossing(&self, settings: &Settings, elevation: f64, rising: bool) -> Option<i64> {
+        crossing(self.date(settings), self.lat, self.lon, elevation, rising)
+    }
+
+    // "start-end" for the morning and evening windows between two elevations
+    fn windows(&self, settings: &Settings, low: f64, high: f64) -> Option<Value> {
+        let time = |timestamp: i64| settings.time(timestamp, self.timezone);
+
+        let morning = (
+            self.crossing(settings, low, true)?,
+            self.crossing(settings, high, true)?,
+        );
+        let evening = (
+            self.crossing(settings, high, false)?,
+            self.crossing(settings, low, false)?,
+        );
+
+        Some(Value::text(format!(
+            "{}-{}, {}-{}",
+            time(morning.0),
+            time(morning.1),
+            time(evening.0),
+            time(evening.1)
+        )))
+    }
+
+    // Looks up a single print option, or None if the option is not known.
+    // Events that do not happen on the date, e.g. during polar day, are Value::Null
+    pub fn field(&self, opt: &str, settings: &Settings) -> Option<Field> {
+        let time = |elevation: f64, rising: bool| {
+            self.crossing(settings, elevation, rising)
+                .map(|timestamp| Value::text(settings.time(timestamp, self.timezone)))
+        };
+        let (elevation, azimuth) = solar_position(self.timestamp, self.lat, self.lon);
+
+        let value = match opt {
+            "date" => Some(Value::text(self.date(settings))),
+            "sunrise" => time(SUNRISE, true),
+            "sunset" => time(SUNRISE, false),
+            "solar_noon" => Some(Value::text(
+                settings.time(solar_noon(self.date(settings), self.lon), self.timezone),
+            )),
+            "day_length" => match (
+                self.crossing(settings, SUNRISE, true),
+                self.crossing(settings, SUNRISE, false),
+            ) {
+                (Some(sunrise), Some(sunset)) => Some(Value::text(to_duration(sunset - sunrise))),
+                // Polar day or night, depending on whether the sun is up at noon
+                _ => {
+                    let noon = solar_noon(self.date(settings), self.lon);
+                    let (elevation, _) = solar_position(noon, self.lat, self.lon);
+                    Some(Value::text(to_duration(match elevation > SUNRISE {
+                        true => 24 * 3600,
+                        false => 0,
+                    })))
+                }
+            },
+            "civil_dawn" => time(CIVIL, true),
+            "civil_dusk" => time(CIVIL, false),
+            "nautical_dawn" => time(NAUTICAL, true),
+            "nautical_dusk" => time(NAUTICAL, false),
+            "astronomical_dawn" => time(ASTRONOMICAL, true),
+            "astronomical_dusk" => time(ASTRONOMICAL, false),
+            "twilight" => self.windows(settings, CIVIL, SUNRISE),
+            "golden_hour" => self.windows(settings, SUNRISE, GOLDEN_HOUR),
+            "blue_hour" => self.windows(settings, CIVIL, BLUE_HOUR),
+            "solar_elevation" => Some(Value::number(format!("{:.2}", elevation), "°")),
+            "solar_azimuth" => Some(Value::number(format!("{:.2}", azimuth), "°")),
+            "moon_phase" => Some(Value::text(moon_phase(self.timestamp))),
+            "moon_illumination" => Some(Value::number(
+                format!("{:.0}", moon_illumination(self.timestamp) * 100.0),
+                "%",
+            )),
+            "moon_age" => Some(Value::number(
+                format!("{:.1}", moon_age(self.timestamp)),
+                "d",
+            )),
+            _ => return None,
+        };
+
+        Some(Field::new(opt, label(opt)?, value.into()))
+    }
+}
+
+impl Dataset for Astro {
+    // The requested options, or every option when none are given
+    fn records(&self, opts: &[&str], args: &Args, config: &Config) -> Vec<Record> {
+        let settings = Settings::new(args, config);
+        let opts = match opts.is_empty() {
+            true => OPTIONS,
+            false => opts,
+        };
+
+        let mut record = Record::default();
+        for opt in opts {
+            record.push(
+                self.field(opt, &settings)
+                    .unwrap_or_else(|| Field::new(opt, opt, Value::Null)),
+            );
+        }
+        vec![record]
+    }
+
+    fn single(&self) -> bool {
+        true
+    }
+
+    // Every print option plus the coordinates, for use in templates
+    fn template_records(&self, args: &Args, config: &Config) -> Vec<Record> {
+        self.records(&[], args, config)
+            .into_iter()
+            .map(|mut record| {
+                record.push(Field::new("lat", "Latitude", Value::number(self.lat, "")));
+                record.push(Field::new("lon", "Longitude", Value::number(self.lon, "")));
+                record
+            })
+            .collect()
+    }
+
+    // Report of the day's sun and moon. Lines for events that do not happen
+    // on the date are left out
+    fn summary(&self, summary: Summary, args: &Args, config: &Config) -> String {
+        let settings = Settings::new(args, config);
+        let value = |opt: &str| -> Value {
+            self.field(opt, &settings)
+                .map(|field| field.value)
+                .unwrap_or(Value::Null)
+        };
+
+        let zone = match (settings.tz, self.timezone) {
+            (Tz::Location, Some(offset)) => format!(" (UTC{:+})", offset / 3600),
+            (Tz::Utc, _) => " (UTC)".to_string(),
+            _ => String::new(),
+        };
+
+        if summary == Summary::Short {
+            let mut parts = vec![];
+            for (label, opt) in [("Sunrise", "sunrise"), ("sunset", "sunset")] {
+                let value = value(opt);
+                if !value.is_null() {
+                    parts.push(format!("{} {}", label, value));
+                }
+            }
+            parts.push(format!(
+                "{} ({})",
+                value("moon_phase"),
+                value("moon_illumination")
+            ));
+
+            return format!("{}{}: {}\n", value("date"), zone, parts.join(", "));
+        }
+
+        let mut lines = vec![format!("{}{}", value("date"), zone)];
+        for opt in [
+            "sunrise",
+            "sunset",
+            "solar_noon",
+            "day_length",
+            "twilight",
+            "golden_hour",
+            "blue_hour",
+            "nautical_dawn",
+            "nautical_dusk",
+            "astronomical_dawn",
+            "astronomical_dusk",
+        ] {
+            let value = value(opt);
+            if !value.is_null() {
+                lines.push(format!("{}: {}", label(opt).unwrap_or(opt), value));
+            }
+        }
+
+        lines.push(format!(
+            "Sun: {} elevation, {} azimuth",
+            value("solar_elevation"),
+            value("solar_azimuth")
+        ));
+        lines.push(format!(
+            "Moon: {}, {} illuminated, {} old",
+            value("moon_phase"),
+            value("moon_illumination"),
+            value("moon_age")
+        ));
+
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+}
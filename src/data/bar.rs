@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{
+    client::error::Error,
+    options::{
+        config::{Band, BarConfig},
+        format::Output,
+    },
+};
+
+use super::{
+    record::{Record, Value},
+    template::Template,
+};
+
+const TEXT: &str = "{temp:.0}{unit_temp} {description}";
+const SHORT_TEXT: &str = "{temp:.0}{unit_temp}";
+const TOOLTIP: &str = "{place}\n{description}\nFeels like {feels_like:.0}{unit_feels_like}\nHumidity {humidity}{unit_humidity}\nWind {wind_speed:.0}{unit_wind_speed} {wind_dir_compass}";
+const PERCENTAGE: &str = "humidity";
+
+#[derive(Serialize)]
+struct Waybar {
+    text: String,
+    tooltip: String,
+    class: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    percentage: Option<u8>,
+}
+
+// Groups OpenWeather condition codes, see https://openweathermap.org/weather-conditions
+pub fn condition(id: i32) -> &'static str {
+    match id {
+        200..=299 => "thunderstorm",
+        300..=399 => "drizzle",
+        500..=599 => "rain",
+        600..=699 => "snow",
+        700..=799 => "atmosphere",
+        800 => "clear",
+        801..=899 => "clouds",
+        _ => "unknown",
+    }
+}
+
+fn band(class: &str, below: Option<f32>, color: &str) -> Band {
+    Band {
+        below,
+        class: class.to_string(),
+        color: Some(color.to_string()),
+    }
+}
+
+// Default bands, in whichever scale the temperature is displayed in
+fn default_bands(unit: &str) -> Vec<Band> {
+    let limits = match unit {
+        "°F" => [32.0, 50.0, 68.0, 86.0],
+        "°K" => [273.15, 283.15, 293.15, 303.15],
+        _ => [0.0, 10.0, 20.0, 30.0],
+    };
+
+    vec![
+        band("freezing", Some(limits[0]), "#88c0d0"),
+        band("cold", Some(limits[1]), "#81a1c1"),
+        band("mild", Some(limits[2]), "#a3be8c"),
+        band("warm", Some(limits[3]), "#ebcb8b"),
+        band("hot", None, "#bf616a"),
+    ]
+}
+
+fn field(record: &Record, name: &str) -> Value {
+    record
+        .fields
+        .iter()
+        .find(|field| field.name == name)
+        .map(|field| field.value.clone())
+        .unwrap_or(Value::Null)
+}
+
+fn number(value: &Value) -> Option<f32> {
+    match value {
+        Value::Number { value, .. } => value.parse().ok(),
+        _ => None,
+    }
+}
+
+pub struct Bar {
+    text: Template,
+    short_text: Template,
+    tooltip: Template,
+    percentage: String,
+    bands: Option<Vec<Band>>,
+    colors: HashMap<String, String>,
+}
+
+impl Bar {
+    pub fn new(config: Option<BarConfig>) -> Result<Self, Error> {
+        let config = config.unwrap_or_default();
+
+        Ok(Self {
+            text: Template::parse(config.text.as_deref().unwrap_or(TEXT))?,
+            short_text: Template::parse(config.short_text.as_deref().unwrap_or(SHORT_TEXT))?,
+            tooltip: Template::parse(config.tooltip.as_deref().unwrap_or(TOOLTIP))?,
+            percentage: config.percentage.unwrap_or(PERCENTAGE.to_string()),
+            bands: config.bands,
+            colors: config.colors.unwrap_or_default(),
+        })
+    }
+
+    fn band(&self, record: &Record) -> Option<Band> {
+        let temp = field(record, "temp");
+        let unit = match &temp {
+            Value::Number { unit, .. } => unit.to_owned(),
+            _ => String::new(),
+        };
+        let temp = number(&temp)?;
+
+        let bands = match &self.bands {
+            Some(bands) => bands.clone(),
+            None => default_bands(&unit),
+        };
+
+        bands
+            .into_iter()
+            .find(|band| band.below.is_none_or(|below| temp < below))
+    }
+
+    // CSS classes (condition group, then temperature band) and the colour to
+    // use. Colours configured for a condition take precedence over the band's
+    fn style(&self, record: &Record) -> (Vec<String>, Option<String>) {
+        let condition = number(&field(record, "id")).map(|id| condition(id as i32));
+        let band = self.band(record);
+
+        let mut classes = vec![];
+        classes.extend(condition.map(str::to_string));
+        classes.extend(band.as_ref().map(|band| band.class.to_owned()));
+
+        let color = condition
+            .and_then(|condition| self.colors.get(condition).cloned())
+            .or_else(|| band.and_then(|band| band.color));
+
+        (classes, color)
+    }
+
+    pub fn render(&self, output: Output, record: &Record) -> Result<String, Error> {
+        let text = self.text.render(record)?;
+        let (classes, color) = self.style(record);
+
+        match output {
+            Output::Waybar => {
+                let percentage = number(&field(record, &self.percentage))
+                    .map(|percentage| percentage.clamp(0.0, 100.0).round() as u8);
+
+                let waybar = Waybar {
+                    text,
+                    tooltip: self.tooltip.render(record)?,
+                    class: classes,
+                    percentage,
+                };
+
+                Ok(serde_json::to_string(&waybar)? + "\n")
+            }
+            Output::I3blocks => {
+                // full_text, short_text and color, one per line
+                let mut lines = vec![text, self.short_text.render(record)?];
+                lines.extend(color);
+
+                Ok(lines.iter().map(|line| format!("{}\n", line)).collect())
+            }
+            Output::Polybar => match color {
+                Some(color) => Ok(format!("%{{F{}}}{}%{{F-}}\n", color, text)),
+                None => Ok(format!("{}\n", text)),
+            },
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use std::{path::Path, sync::Arc};
+
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::{
+    client::{client::OpenWeatherClient, error::Error},
+    data::{
+        current_weather::CurrentWeather,
+        data::Dataset,
+        record::{Field, Record, Value},
+        template::Template,
+    },
+    options::{
+        args::Args,
+        config::{Config, Location},
+        format::Summary,
+    },
+};
+
+// Print options compared when no --print is given
+pub const OPTIONS: &[&str] = &[
+    "temp",
+    "feels_like",
+    "humidity",
+    "wind_speed",
+    "description",
+];
+
+// A location of a batch, with the configuration to fetch it with
+pub struct Entry {
+    pub label: String,
+    pub config: Config,
+}
+
+// The saved locations named with --location, followed by the rows of
+// --locations-file
+pub fn entries(args: &Args, config: &Config) -> Result<Vec<Entry>, Error> {
+    let mut entries = vec![];
+
+    for name in args.location.iter() {
+        entries.push(Entry {
+            label: name.to_owned(),
+            config: config.clone().for_location(Some(name), true)?,
+        });
+    }
+
+    if let Some(path) = &args.locations_file {
+        for location in read_locations(path)? {
+            let label = location.name.clone().unwrap_or_else(|| location.describe());
+            entries.push(Entry {
+                config: config.clone().with_location(&label, location),
+                label,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+// A CSV file with a header row naming its columns: name, and either lat and
+// lon or city, state, country and zip, plus optional units and lang. Empty
+// cells and missing columns are left unset, and lines starting with # skipped
+fn read_locations(path: &Path) -> Result<Vec<Location>, Error> {
+    let error =
+        |e: &dyn std::fmt::Display| Error::LocationsFile(format!("{}: {}", path.display(), e));
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_path(path)
+        .map_err(|e| error(&e))?;
+
+    let mut locations = vec![];
+    for (n, row) in reader.deserialize::<Location>().enumerate() {
+        let location = row.map_err(|e| error(&e))?;
+        let name = match &location.name {
+            Some(name) => name.to_owned(),
+            None => format!("row {}", n + 1),
+        };
+        location.validate(&name).map_err(|e| error(&e))?;
+        locations.push(location);
+    }
+
+    Ok(locations)
+}
+
+// The current weather of several locations, in the order they were given.
+// Locations that could not be fetched keep their error
+pub struct Batch {
+    pub results: Vec<(Entry, Result<CurrentWeather, Error>)>,
+}
+
+impl Batch {
+    // Fetches every entry concurrently, at most `limit` at a time
+    pub async fn get(
+        client: &OpenWeatherClient,
+        args: &Args,
+        entries: Vec<Entry>,
+        limit: usize,
+    ) -> Self {
+        let semaphore = Arc::new(Semaphore::new(limit));
+        let args = Arc::new(args.clone());
+        let mut tasks = JoinSet::new();
+
+        for (n, entry) in entries.iter().enumerate() {
+            let (client, args, config) = (client.clone(), args.clone(), entry.config.clone());
+            let semaphore = semaphore.clone();
+
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await.expect("Semaphore closed!");
+                (n, CurrentWeather::fetch(&client, &args, &config).await)
+            });
+        }
+
+        let mut results: Vec<Option<Result<CurrentWeather, Error>>> =
+            entries.iter().map(|_| None).collect();
+        while let Some(task) = tasks.join_next().await {
+            let (n, result) = task.expect("Could not fetch location!");
+            results[n] = Some(result);
+        }
+
+        Self {
+            results: entries
+                .into_iter()
+                .zip(results)
+                .map(|(entry, result)| (entry, result.expect("Location was not fetched!")))
+                .collect(),
+        }
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .count()
+    }
+
+    // One record per location, led by its label and ending with the error of
+    // a location that failed, or null
+    pub fn records(&self, opts: &[&str], args: &Args) -> Vec<Record> {
+        let opts = match opts.is_empty() {
+            true => OPTIONS,
+            false => opts,
+        };
+
+        self.results
+            .iter()
+            .map(|(entry, result)| {
+                let mut record = Record::default();
+                record.push(Field::new(
+                    "location",
+                    "Location",
+                    Value::text(&entry.label),
+                ));
+
+                match result {
+                    Ok(weather) => record
+                        .fields
+                        .extend(weather.record(opts, args, &entry.config).fields),
+                    Err(_) => {
+                        for opt in opts {
+                            record.push(Field::new(opt, opt, Value::Null));
+                        }
+                    }
+                }
+
+                record.push(Field::new(
+                    "error",
+                    "Error",
+                    result
+                        .as_ref()
+                        .err()
+                        .map(|e| Value::text(e.to_string()))
+                        .into(),
+                ));
+                record
+            })
+            .collect()
+    }
+
+    // Comparison table with a column per option, for --format text. The error
+    // column is only shown when a location failed
+    pub fn table(&self, opts: &[&str], args: &Args) -> String {
+        let records = self.records(opts, args);
+        let columns = match self.failed() {
+            0 => records.first().map_or(0, |record| record.fields.len() - 1),
+            _ => records.first().map_or(0, |record| record.fields.len()),
+        };
+
+        let mut rows: Vec<Vec<String>> = vec![];
+        if let Some(record) = records.first() {
+            rows.push(
+                record
+                    .fields
+                    .iter()
+                    .map(|field| field.name.clone())
+                    .collect(),
+            );
+        }
+        for record in records.iter() {
+            rows.push(
+                record
+                    .fields
+                    .iter()
+                    .map(|field| field.value.to_string())
+                    .collect(),
+            );
+        }
+
+        let widths: Vec<usize> = (0..columns)
+            .map(|column| {
+                rows.iter()
+                    .map(|row| row[column].chars().count())
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        rows.iter()
+            .map(|row| {
+                let line: Vec<String> = widths
+                    .iter()
+                    .zip(row)
+                    .map(|(width, cell)| format!("{:width$}", cell, width = width))
+                    .collect();
+                format!("{}\n", line.join("  ").trim_end())
+            })
+            .collect()
+    }
+
+    // Each location's summary, separated by blank lines unless short
+    pub fn summary(&self, summary: Summary, args: &Args) -> String {
+        let separator = match summary {
+            Summary::Short => "",
+            _ => "\n",
+        };
+
+        self.results
+            .iter()
+            .map(|(entry, result)| match result {
+                Ok(weather) => weather.summary(summary, args, &entry.config),
+                Err(e) => format!("{}: Error: {}\n", entry.label, e),
+            })
+            .collect::<Vec<String>>()
+            .join(separator)
+    }
+
+    // The template rendered for each location, which it can refer to as
+    // {location}. Errors are reported on stderr
+    pub fn render(&self, template: &Template, args: &Args) -> Result<String, Error> {
+        let mut output = String::new();
+
+        for (entry, result) in self.results.iter() {
+            match result {
+                Ok(weather) => {
+                    let mut record = weather.template_record(args, &entry.config);
+                    record.push(Field::new(
+                        "location",
+                        "Location",
+                        Value::text(&entry.label),
+                    ));
+                    output += &format!("{}\n", template.render(&record)?);
+                }
+                Err(e) => eprintln!("Error: {}: {}", entry.label, e),
+            }
+        }
+
+        Ok(output)
+    }
+}
@@ -1,3 +1,10 @@
+use chrono::{
+    format::{Item, StrftimeItems},
+    FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone,
+};
+
+use crate::client::error::Error;
+
 // Kelvin -> Celsius
 pub fn to_celsius(temp: f32) -> f32 {
     temp - 273.15
@@ -17,3 +24,211 @@ pub fn to_mph(speed: f32) -> f32 {
 pub fn to_inches(precipitation: f32) -> f32 {
     precipitation / 25.4
 }
+
+// Unix timestamp -> strftime format, at the given UTC offset in seconds or,
+// without one, in the local zone
+pub fn to_datetime(timestamp: i64, offset: Option<i32>, format: &str) -> String {
+    let datetime = match offset {
+        Some(offset) => FixedOffset::east_opt(offset)
+            .and_then(|tz| tz.timestamp_opt(timestamp, 0).single())
+            .map(|datetime| datetime.format(format).to_string()),
+        None => Local
+            .timestamp_opt(timestamp, 0)
+            .single()
+            .map(|datetime| datetime.format(format).to_string()),
+    };
+
+    datetime.unwrap_or_default()
+}
+
+// UTC offset in seconds of the local zone at the given Unix timestamp
+pub fn local_offset(timestamp: i64) -> i32 {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|datetime| datetime.offset().fix().local_minus_utc())
+        .unwrap_or(0)
+}
+
+// Longitude -> UTC offset in seconds of its nautical time zone, which stands
+// in for the location's offset when no API response provides one
+pub fn nautical_offset(lon: f64) -> i32 {
+    (lon / 15.0).round() as i32 * 3600
+}
+
+// Date, which means noon, or date and time as given to --date, --start or --end
+pub fn parse_date(date: &str) -> Result<NaiveDateTime, Error> {
+    let invalid = || Error::InvalidDate(date.to_string());
+
+    match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        Ok(date) => Ok(date.and_time(NaiveTime::from_hms_opt(12, 0, 0).ok_or_else(invalid)?)),
+        Err(_) => ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S"]
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(date, format).ok())
+            .ok_or_else(invalid),
+    }
+}
+
+// Seconds -> e.g. "14h 42m"
+pub fn to_duration(seconds: i64) -> String {
+    format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60)
+}
+
+// chrono panics when displaying an invalid format, so formats from the user
+// are checked before anything is printed
+pub fn check_time_format(format: &str) -> Result<(), Error> {
+    match StrftimeItems::new(format).any(|item| item == Item::Error) {
+        true => Err(Error::TimeFormat(format.to_string())),
+        false => Ok(()),
+    }
+}
+
+// Degrees -> 8, 16 or 32 point compass direction
+pub fn to_compass_points(deg: f32, points: usize) -> &'static str {
+    const POINTS: [&str; 32] = [
+        "N", "NbE", "NNE", "NEbN", "NE", "NEbE", "ENE", "EbN", "E", "EbS", "ESE", "SEbE", "SE",
+        "SEbS", "SSE", "SbE", "S", "SbW", "SSW", "SWbS", "SW", "SWbW", "WSW", "WbS", "W", "WbN",
+        "WNW", "NWbW", "NW", "NWbN", "NNW", "NbW",
+    ];
+    let index = (deg.rem_euclid(360.0) / (360.0 / points as f32)).round() as usize % points;
+    POINTS[index * (32 / points)]
+}
+
+// Degrees -> 16 point compass direction
+pub fn to_compass(deg: f32) -> &'static str {
+    to_compass_points(deg, 16)
+}
+
+// Language as given to --lang -> the code OpenWeather uses, e.g. "de_DE" -> "de"
+pub fn language(lang: &str) -> String {
+    lang.split(['-', '_']).next().unwrap_or(lang).to_lowercase()
+}
+
+// City name and country code -> e.g. "Mountain View, US", or whichever of the
+// two is known
+pub fn place(name: Option<String>, country: Option<String>) -> Option<String> {
+    match (name, country) {
+        (Some(name), Some(country)) if !name.is_empty() => Some(format!("{}, {}", name, country)),
+        (Some(name), None) if !name.is_empty() => Some(name),
+        (_, country) => country,
+    }
+}
+
+// How a language names the 32 point directions between the 16 point ones
+enum By {
+    // A letter between the two points, as in English "NbE", north by east
+    Letter(&'static str),
+    // A quarter from a point towards the nearest 8 point direction, as in
+    // French "N¼NE", nord quart nord-est
+    Quarter,
+}
+
+// The north, east, south and west abbreviations of each language, and how it
+// names the points in between
+fn compass_words(lang: &str) -> Option<([&'static str; 4], By)> {
+    let words = match lang {
+        "de" => (["N", "O", "S", "W"], By::Letter("z")),
+        "nl" => (["N", "O", "Z", "W"], By::Letter("t")),
+        "fr" => (["N", "E", "S", "O"], By::Quarter),
+        "es" => (["N", "E", "S", "O"], By::Quarter),
+        "it" => (["N", "E", "S", "O"], By::Quarter),
+        "pt" => (["N", "E", "S", "O"], By::Quarter),
+        "ca" => (["N", "E", "S", "O"], By::Quarter),
+        "ru" => (["С", "В", "Ю", "З"], By::Letter("т")),
+        "uk" => (["Пн", "Сх", "Пд", "Зх"], By::Quarter),
+        _ => return None,
+    };
+    Some(words)
+}
+
+// Compass direction -> the abbreviations used in the given language, e.g.
+// "ENE" -> "ONO" in German. Unknown languages are left in English
+pub fn localize_compass(point: &str, lang: &str) -> String {
+    let Some((cardinals, by)) = compass_words(&language(lang)) else {
+        return point.to_string();
+    };
+    let localize = |point: &str| -> String {
+        point
+            .chars()
+            .map(|c| match c {
+                'N' => cardinals[0],
+                'E' => cardinals[1],
+                'S' => cardinals[2],
+                _ => cardinals[3],
+            })
+            .collect()
+    };
+
+    match (point.split_once('b'), by) {
+        (None, _) => localize(point),
+        (Some((from, towards)), By::Letter(by)) => localize(from) + by + &localize(towards),
+        (Some((from, towards)), By::Quarter) => {
+            // NbE -> N¼NE and NEbN -> NE¼N, with north or south first in NE
+            let nearest = match (from.len(), from) {
+                (1, "N" | "S") => format!("{}{}", from, towards),
+                (1, _) => format!("{}{}", towards, from),
+                _ => towards.to_string(),
+            };
+            localize(from) + "¼" + &localize(&nearest)
+        }
+    }
+}
+
+// Degrees the wind blows from -> arrow pointing the way it blows to
+pub fn to_arrow(deg: f32) -> &'static str {
+    const ARROWS: [&str; 8] = ["↓", "↙", "←", "↖", "↑", "↗", "→", "↘"];
+    ARROWS[(deg.rem_euclid(360.0) / 45.0).round() as usize % 8]
+}
+
+// Celsius -> Kelvin
+pub fn from_celsius(temp: f32) -> f32 {
+    temp + 273.15
+}
+
+// Fahrenheight -> Kelvin
+pub fn from_fahrenheight(temp: f32) -> f32 {
+    (temp + 459.67) * (5.0 / 9.0)
+}
+
+// mph -> m/s
+pub fn from_mph(speed: f32) -> f32 {
+    speed / 2.23694
+}
+
+// m/s -> km/h
+pub fn to_kmh(speed: f32) -> f32 {
+    speed * 3.6
+}
+
+// m/s -> knots
+pub fn to_knots(speed: f32) -> f32 {
+    speed * 1.943844
+}
+
+// m/s -> Beaufort force, using the upper limits of each force
+pub fn to_beaufort(speed: f32) -> u8 {
+    const LIMITS: [f32; 12] = [
+        0.5, 1.6, 3.4, 5.5, 8.0, 10.8, 13.9, 17.2, 20.8, 24.5, 28.5, 32.7,
+    ];
+    LIMITS.iter().take_while(|limit| speed >= **limit).count() as u8
+}
+
+// hPa -> inHg
+pub fn to_inhg(pressure: f32) -> f32 {
+    pressure * 0.02953
+}
+
+// hPa -> mmHg
+pub fn to_mmhg(pressure: f32) -> f32 {
+    pressure * 0.750062
+}
+
+// m -> km
+pub fn to_km(distance: f32) -> f32 {
+    distance / 1000.0
+}
+
+// m -> mi
+pub fn to_miles(distance: f32) -> f32 {
+    distance / 1609.344
+}
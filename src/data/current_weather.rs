@@ -1,24 +1,71 @@
 use crate::{
-    data::convert::{to_celsius, to_fahrenheight, to_inches, to_mph},
+    client::{client::OpenWeatherClient, error::Error},
+    data::{
+        astro::{self, Astro},
+        convert::{place, to_compass, to_duration},
+        derived::{self, Conditions},
+        icons::{glyph, is_night},
+        record::{capitalize, Field, Record, Value},
+    },
     options::{
         args::Args,
         config::Config,
-        options::{
-            get_city, get_country, get_key, get_lat, get_lon, get_state, get_units, get_zip,
-        },
+        format::{Icons, Summary},
+        options::get_units,
+        settings::Settings,
     },
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use super::{data::Data, geocoding::Geocoding};
+use super::{
+    data::{Data, Dataset},
+    geocoding::{Geocoding, GeocodingData},
+};
 
-#[derive(Deserialize, Clone)]
+// Every print option, in the order used when no --print is given
+pub const OPTIONS: &[&str] = &[
+    "lat",
+    "lon",
+    "id",
+    "weather",
+    "description",
+    "icon",
+    "temp",
+    "feels_like",
+    "temp_min",
+    "temp_max",
+    "pressure",
+    "humidity",
+    "visibility",
+    "wind_speed",
+    "wind_dir",
+    "wind_gust",
+    "rain_1h",
+    "rain_3h",
+    "snow_1h",
+    "snow_3h",
+    "clouds",
+    "sunrise",
+    "sunset",
+    "day_length",
+    "observed_at",
+    "local_time",
+    "glyph",
+    "dew_point",
+    "heat_index",
+    "wind_chill",
+    "humidex",
+    "absolute_humidity",
+    "wet_bulb",
+];
+
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Coord {
     pub lon: Option<f64>,
     pub lat: Option<f64>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Weather {
     pub id: Option<i32>,
     pub main: Option<String>,
@@ -26,7 +73,7 @@ pub struct Weather {
     pub icon: Option<String>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Main {
     pub temp: Option<f32>,
     pub feels_like: Option<f32>,
@@ -38,31 +85,35 @@ pub struct Main {
     pub grnd_level: Option<i32>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Wind {
     pub speed: Option<f32>,
     pub deg: Option<i32>,
     pub gust: Option<f32>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Rain {
+    #[serde(rename = "1h")]
     pub _1h: Option<f32>,
+    #[serde(rename = "3h")]
     pub _3h: Option<f32>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Snow {
+    #[serde(rename = "1h")]
     pub _1h: Option<f32>,
+    #[serde(rename = "3h")]
     pub _3h: Option<f32>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Clouds {
     pub all: Option<i32>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Sys {
     pub r#type: Option<i32>,
     pub id: Option<i32>,
@@ -71,7 +122,7 @@ pub struct Sys {
     pub sunset: Option<i32>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct CurrentWeather {
     pub coord: Option<Coord>,
     pub weather: Option<Vec<Weather>>,
@@ -88,716 +139,420 @@ pub struct CurrentWeather {
     pub id: Option<i32>,
     pub name: Option<String>,
     pub cod: Option<i32>,
+    // Nearest named place from reverse geocoding, when --print place asks for it
+    #[serde(skip)]
+    pub nearby: Option<GeocodingData>,
 }
 
 impl CurrentWeather {
-    pub async fn get(args: &Args, config: &Config) -> Result<Data, reqwest::Error> {
-        let key = get_key(args, config);
-        let lat = get_lat(args, config);
-        let lon = get_lon(args, config);
-        let city = get_city(args, config);
-        let state = get_state(args, config);
-        let country = get_country(args, config);
-        let zip = get_zip(args, config);
-
-        let key = match key {
-            Some(key) => key,
-            None => panic!("No API key found!"),
-        };
+    pub async fn get(
+        client: &OpenWeatherClient,
+        args: &Args,
+        config: &Config,
+    ) -> Result<Data, Error> {
+        Ok(Data::CurrentWeather(
+            Self::fetch(client, args, config).await?,
+        ))
+    }
 
-        let (lat, lon) = match (lat, lon) {
-            (Some(lat), Some(lon)) => (lat, lon),
-            _ => {
-                let geocoding = Geocoding::get(&key, city, state, country, zip).await?;
-
-                match geocoding {
-                    Some(data) => match (data.lat, data.lon) {
-                        (Some(lat), Some(lon)) => (lat, lon),
-                        _ => (0.0, 0.0),
-                    },
-                    None => (0.0, 0.0),
-                }
+    pub async fn fetch(
+        client: &OpenWeatherClient,
+        args: &Args,
+        config: &Config,
+    ) -> Result<Self, Error> {
+        let (lat, lon) = Geocoding::locate(client, args, config).await?;
+
+        let units = get_units(args, config).unwrap_or_default();
+
+        let query = [
+            ("lat", lat.to_string()),
+            ("lon", lon.to_string()),
+            ("units", units.query().to_string()),
+        ];
+        let mut data: CurrentWeather = client.get("/data/2.5/weather", &query).await?;
+
+        // Reverse geocoding costs another request, so it is only done when the
+        // place is printed
+        let print = args.print.as_deref().unwrap_or_default();
+        if print.split(',').any(|opt| opt == "place") {
+            data.nearby = Geocoding::reverse(client, lat, lon, 1)
+                .await?
+                .into_iter()
+                .next();
+        }
+
+        Ok(data)
+    }
+
+    fn weather(&self) -> Option<Weather> {
+        self.weather
+            .as_ref()
+            .and_then(|weather| weather.first().cloned())
+    }
+
+    // Prefers the day/night suffix of the icon, falling back to comparing the
+    // observation time with sunrise and sunset
+    fn is_night(&self) -> Option<bool> {
+        if let Some(icon) = self.weather().and_then(|w| w.icon) {
+            return Some(is_night(&icon));
+        }
+
+        let sys = self.sys.clone()?;
+        let (dt, sunrise, sunset) = (self.dt?, sys.sunrise?, sys.sunset?);
+        Some(dt < sunrise || dt >= sunset)
+    }
+
+    fn astro(&self) -> Option<Astro> {
+        let coord = self.coord.clone()?;
+        Some(Astro {
+            lat: coord.lat?,
+            lon: coord.lon?,
+            timestamp: self.dt? as i64,
+            timezone: self.timezone,
+        })
+    }
+
+    fn conditions(&self, settings: &Settings) -> Conditions {
+        let main = self.main.clone();
+        Conditions::new(
+            main.as_ref().and_then(|m| m.temp),
+            main.and_then(|m| m.humidity),
+            self.wind.clone().and_then(|w| w.speed),
+            &settings.units,
+        )
+    }
+
+    // Looks up a single print option, or None if the option is not known.
+    // Known options whose data is missing from the response are Value::Null
+    pub fn field(&self, opt: &str, settings: &Settings) -> Option<Field> {
+        let units = &settings.units;
+        let coord = self.coord.clone();
+        let weather = self.weather();
+        let main = self.main.clone();
+        let wind = self.wind.clone();
+        let rain = self.rain.clone();
+        let snow = self.snow.clone();
+        let sys = self.sys.clone();
+
+        let (label, value) = match opt {
+            "lat" => (
+                "Latitude",
+                coord.and_then(|c| c.lat).map(|lat| Value::number(lat, "")),
+            ),
+            "lon" => (
+                "Longitude",
+                coord.and_then(|c| c.lon).map(|lon| Value::number(lon, "")),
+            ),
+            "id" => (
+                "Weather ID",
+                weather.and_then(|w| w.id).map(|id| Value::number(id, "")),
+            ),
+            "weather" => (
+                "Current weather",
+                weather.and_then(|w| w.main).map(Value::text),
+            ),
+            "description" => (
+                "Weather description",
+                weather.and_then(|w| w.description).map(Value::text),
+            ),
+            "icon" => (
+                "Weather Icon",
+                weather.and_then(|w| w.icon).map(Value::text),
+            ),
+            "temp" => (
+                "Current Temperature",
+                main.and_then(|m| m.temp).map(|t| Value::temp(t, units)),
+            ),
+            "feels_like" => (
+                "Wind Chill",
+                main.and_then(|m| m.feels_like)
+                    .map(|t| Value::temp(t, units)),
+            ),
+            "temp_min" => (
+                "Low Temperature",
+                main.and_then(|m| m.temp_min).map(|t| Value::temp(t, units)),
+            ),
+            "temp_max" => (
+                "High Temperature",
+                main.and_then(|m| m.temp_max).map(|t| Value::temp(t, units)),
+            ),
+            "pressure" => (
+                "Pressure",
+                main.and_then(|m| m.pressure)
+                    .map(|p| Value::pressure(p as f32, units)),
+            ),
+            "humidity" => (
+                "Humidity",
+                main.and_then(|m| m.humidity).map(|h| Value::number(h, "%")),
+            ),
+            "visibility" => (
+                "Visibility",
+                self.visibility.map(|v| Value::distance(v as f32, units)),
+            ),
+            "wind_speed" => (
+                "Wind Speed",
+                wind.and_then(|w| w.speed).map(|s| Value::speed(s, units)),
+            ),
+            "wind_dir" => (
+                "Wind Direction",
+                wind.and_then(|w| w.deg).map(|d| settings.wind_dir(d)),
+            ),
+            "wind_gust" => (
+                "Wind Gust",
+                wind.and_then(|w| w.gust).map(|s| Value::speed(s, units)),
+            ),
+            "rain_1h" => (
+                "Rainfall 1hr",
+                rain.and_then(|r| r._1h)
+                    .map(|r| Value::precipitation(r, units)),
+            ),
+            "rain_3h" => (
+                "Rainfall 3hr",
+                rain.and_then(|r| r._3h)
+                    .map(|r| Value::precipitation(r, units)),
+            ),
+            "snow_1h" => (
+                "Snowfall 1hr",
+                snow.and_then(|s| s._1h)
+                    .map(|s| Value::precipitation(s, units)),
+            ),
+            "snow_3h" => (
+                "Snowfall 3hr",
+                snow.and_then(|s| s._3h)
+                    .map(|s| Value::precipitation(s, units)),
+            ),
+            "clouds" => (
+                "Clouds",
+                self.clouds
+                    .clone()
+                    .and_then(|c| c.all)
+                    .map(|c| Value::number(c, "%")),
+            ),
+            "glyph" => (
+                "Weather Glyph",
+                weather.and_then(|w| w.id).map(|id| {
+                    let icons = settings.icons.unwrap_or_default();
+                    Value::text(glyph(id, self.is_night().unwrap_or(false), icons))
+                }),
+            ),
+            "sunrise" => (
+                "Sunrise",
+                sys.as_ref()
+                    .and_then(|s| s.sunrise)
+                    .map(|t| Value::text(settings.time(t as i64, self.timezone))),
+            ),
+            "sunset" => (
+                "Sunset",
+                sys.as_ref()
+                    .and_then(|s| s.sunset)
+                    .map(|t| Value::text(settings.time(t as i64, self.timezone))),
+            ),
+            "day_length" => (
+                "Day Length",
+                sys.as_ref()
+                    .and_then(|s| Some(s.sunset? - s.sunrise?))
+                    .map(|length| Value::text(to_duration(length as i64))),
+            ),
+            "observed_at" => (
+                "Observed At",
+                self.dt
+                    .map(|t| Value::text(settings.datetime(t as i64, self.timezone))),
+            ),
+            "local_time" => (
+                "Local Time",
+                Some(Value::text(
+                    settings.datetime(chrono::Utc::now().timestamp(), self.timezone),
+                )),
+            ),
+            opt if derived::label(opt).is_some() => (
+                derived::label(opt)?,
+                self.conditions(settings)
+                    .metric(opt)
+                    .map(|metric| derived::value(opt, metric, units)),
+            ),
+            "place" => (
+                "Place",
+                self.place(settings.lang.as_deref()).map(Value::text),
+            ),
+            // Astronomical options are computed for the observation
+            opt if astro::label(opt).is_some() => {
+                return self.astro().and_then(|astro| astro.field(opt, settings))
             }
+            _ => return None,
         };
 
-        let req_uri = format!(
-            "https://api.openweathermap.org/data/2.5/weather?lat={}&lon={}&appid={}",
-            lat, lon, key
-        );
+        Some(Field::new(opt, label, value.into()))
+    }
 
-        let response = reqwest::get(req_uri).await?;
+    // Collects the requested options, or every option when none are given
+    pub fn record(&self, opts: &[&str], args: &Args, config: &Config) -> Record {
+        let settings = Settings::new(args, config);
+        let opts = match opts.is_empty() {
+            true => OPTIONS,
+            false => opts,
+        };
 
-        if !response.status().is_success() {
-            println!("Response failed with status code {}", response.status());
-            panic!("Failed to get response, aborting!")
+        let mut record = Record::default();
+        for opt in opts {
+            record.push(
+                self.field(opt, &settings)
+                    .unwrap_or_else(|| Field::new(opt, opt, Value::Null)),
+            );
         }
+        record
+    }
 
-        let body = response.text().await?;
-        let data: CurrentWeather =
-            serde_json::from_str(&body).expect("Failed to deserialize response body!");
+    // Every print option plus values derived from them, for use in templates
+    pub fn template_record(&self, args: &Args, config: &Config) -> Record {
+        let settings = Settings::new(args, config);
+        let mut record = self.record(&[], args, config);
+        let country = self.sys.clone().and_then(|sys| sys.country);
+        let deg = self.wind.clone().and_then(|wind| wind.deg);
+
+        record.push(Field::new(
+            "name",
+            "Name",
+            self.name.clone().map(Value::text).into(),
+        ));
+        record.push(Field::new(
+            "country",
+            "Country",
+            country.map(Value::text).into(),
+        ));
+        record.push(Field::new(
+            "place",
+            "Place",
+            self.place(settings.lang.as_deref()).map(Value::text).into(),
+        ));
+        record.push(Field::new(
+            "wind_dir_compass",
+            "Wind Direction",
+            deg.map(|deg| Value::text(to_compass(deg as f32))).into(),
+        ));
+        record
+    }
+
+    // Prefers the reverse geocoded place, which also names the state
+    fn place(&self, lang: Option<&str>) -> Option<String> {
+        if let Some(place) = self.nearby.as_ref().and_then(|nearby| nearby.place(lang)) {
+            return Some(place);
+        }
 
-        Ok(Data::CurrentWeather(data))
+        let country = self.sys.clone().and_then(|sys| sys.country);
+        place(self.name.clone(), country)
     }
+}
 
-    pub fn print(&self, opt: &str, args: &Args, config: &Config) {
-        let units = get_units(args, config);
+impl Dataset for CurrentWeather {
+    fn records(&self, opts: &[&str], args: &Args, config: &Config) -> Vec<Record> {
+        vec![self.record(opts, args, config)]
+    }
 
-        let units = match units {
-            Some(units) => units,
-            None => "M".to_string(),
-        }
-        .to_uppercase();
-
-        let units = units.as_str();
-
-        match opt {
-            "lat" => match args.verbose {
-                true => println!(
-                    "Latitude: {}",
-                    self.clone()
-                        .coord
-                        .expect("Could not unpack coordinates!")
-                        .lat
-                        .expect("Could not unpack latitude!")
-                ),
-                false => println!(
-                    "{}",
-                    self.clone()
-                        .coord
-                        .expect("Could not unpack coordinates!")
-                        .lat
-                        .expect("Could not unpack latitude!")
-                ),
-            },
-            "lon" => match args.verbose {
-                true => println!(
-                    "Longitude: {}",
-                    self.clone()
-                        .coord
-                        .expect("Could not unpack coordinates!")
-                        .lon
-                        .expect("Could not unpack longitude!")
-                ),
-                false => println!(
-                    "{}",
-                    self.clone()
-                        .coord
-                        .expect("Could not unpack coordinates!")
-                        .lon
-                        .expect("Could not unpack longitude!")
-                ),
-            },
-            "id" => match args.verbose {
-                true => println!(
-                    "Weather ID: {}",
-                    self.clone().weather.expect("Could not unpack weather!")[0]
-                        .id
-                        .clone()
-                        .expect("Could not unpack weather ID!")
-                ),
-                false => println!(
-                    "{}",
-                    self.clone().weather.expect("Could not unpack weather!")[0]
-                        .id
-                        .clone()
-                        .expect("Could not unpack weather ID!")
-                ),
-            },
-            "weather" => match args.verbose {
-                true => println!(
-                    "Current weather: {}",
-                    self.clone().weather.expect("Could not unpack weather!")[0]
-                        .main
-                        .clone()
-                        .expect("Could not unpack main weather type!")
-                ),
-                false => println!(
-                    "{}",
-                    self.clone().weather.expect("Could not unpack weather!")[0]
-                        .main
-                        .clone()
-                        .expect("Could not unpack main weather type!")
-                ),
-            },
-            "description" => match args.verbose {
-                true => println!(
-                    "Weather description: {}",
-                    self.clone().weather.expect("Could not unpack weather!")[0]
-                        .description
-                        .clone()
-                        .expect("Could not unpack weather description!")
-                ),
-                false => println!(
-                    "{}",
-                    self.clone().weather.expect("Could not unpack weather!")[0]
-                        .description
-                        .clone()
-                        .expect("Could not unpack weather description!")
-                ),
-            },
-            "icon" => match args.verbose {
-                true => println!(
-                    "Weather Icon: {}",
-                    self.clone().weather.expect("Could not unpack weather!")[0]
-                        .icon
-                        .clone()
-                        .expect("Could not unpack weather icon!")
-                ),
-                false => println!(
-                    "{}",
-                    self.clone().weather.expect("Could not unpack weather!")[0]
-                        .icon
-                        .clone()
-                        .expect("Could not unpack weather icon!")
-                ),
-            },
-            "temp" => match args.verbose {
-                true => {
-                    let temp = self
-                        .clone()
-                        .main
-                        .expect("Could not unpack main!")
-                        .temp
-                        .expect("Could not unpack current temperature!");
-
-                    let temp = match units {
-                        "M" => to_celsius(temp),
-                        "I" => to_fahrenheight(temp),
-                        _ => temp,
-                    };
-
-                    let units = match units {
-                        "M" => "°C",
-                        "I" => "°F",
-                        _ => "°K",
-                    };
-
-                    println!("Current Temperature: {:.2}{}", temp, units);
-                }
-                false => {
-                    let temp = self
-                        .clone()
-                        .main
-                        .expect("Could not unpack main!")
-                        .temp
-                        .expect("Could not unpack current temperature!");
-
-                    let temp = match units {
-                        "M" => to_celsius(temp),
-                        "I" => to_fahrenheight(temp),
-                        _ => temp,
-                    };
-
-                    let units = match units {
-                        "M" => "°C",
-                        "I" => "°F",
-                        _ => "°K",
-                    };
-
-                    println!("{:.2}{}", temp, units);
-                }
-            },
-            "feels_like" => match args.verbose {
-                true => {
-                    let feels_like = self
-                        .clone()
-                        .main
-                        .expect("Could not unpack main!")
-                        .feels_like
-                        .expect("Could not unpack wind chill!");
-
-                    let feels_like = match units {
-                        "M" => to_celsius(feels_like),
-                        "I" => to_fahrenheight(feels_like),
-                        _ => feels_like,
-                    };
-
-                    let units = match units {
-                        "M" => "°C",
-                        "I" => "°F",
-                        _ => "°K",
-                    };
-
-                    println!("Wind Chill: {:.2}{}", feels_like, units);
-                }
-                false => {
-                    let feels_like = self
-                        .clone()
-                        .main
-                        .expect("Could not unpack main!")
-                        .feels_like
-                        .expect("Could not unpack wind chill!");
-
-                    let feels_like = match units {
-                        "M" => to_celsius(feels_like),
-                        "I" => to_fahrenheight(feels_like),
-                        _ => feels_like,
-                    };
-
-                    let units = match units {
-                        "M" => "°C",
-                        "I" => "°F",
-                        _ => "°K",
-                    };
-
-                    println!("{:.2}{}", feels_like, units);
-                }
-            },
-            "temp_min" => match args.verbose {
-                true => {
-                    let temp = self
-                        .clone()
-                        .main
-                        .expect("Could not unpack main!")
-                        .temp_min
-                        .expect("Could not unpack min temp!");
-
-                    let temp = match units {
-                        "M" => to_celsius(temp),
-                        "I" => to_fahrenheight(temp),
-                        _ => temp,
-                    };
-
-                    let units = match units {
-                        "M" => "°C",
-                        "I" => "°F",
-                        _ => "°K",
-                    };
-
-                    println!("Low Temperature: {:.2}{}", temp, units);
-                }
-                false => {
-                    let temp = self
-                        .clone()
-                        .main
-                        .expect("Could not unpack main!")
-                        .temp_min
-                        .expect("Could not unpack min temp!");
-
-                    let temp = match units {
-                        "M" => to_celsius(temp),
-                        "I" => to_fahrenheight(temp),
-                        _ => temp,
-                    };
-
-                    let units = match units {
-                        "M" => "°C",
-                        "I" => "°F",
-                        _ => "°K",
-                    };
-
-                    println!("{:.2}{}", temp, units);
-                }
-            },
-            "temp_max" => match args.verbose {
-                true => {
-                    let temp = self
-                        .clone()
-                        .main
-                        .expect("Could not unpack main!")
-                        .temp_max
-                        .expect("Could not unpack max temp!");
-
-                    let temp = match units {
-                        "M" => to_celsius(temp),
-                        "I" => to_fahrenheight(temp),
-                        _ => temp,
-                    };
-
-                    let units = match units {
-                        "M" => "°C",
-                        "I" => "°F",
-                        _ => "°K",
-                    };
-
-                    println!("High Temperature: {:.2}{}", temp, units);
-                }
-                false => {
-                    let temp = self
-                        .clone()
-                        .main
-                        .expect("Could not unpack main!")
-                        .temp_max
-                        .expect("Could not unpack max temp!");
-
-                    let temp = match units {
-                        "M" => to_celsius(temp),
-                        "I" => to_fahrenheight(temp),
-                        _ => temp,
-                    };
-
-                    let units = match units {
-                        "M" => "°C",
-                        "I" => "°F",
-                        _ => "°K",
-                    };
-
-                    println!("{:.2}{}", temp, units);
-                }
-            },
-            "pressure" => match args.verbose {
-                true => println!(
-                    "Pressure: {}hPa",
-                    self.clone()
-                        .main
-                        .expect("Could not unpack main!")
-                        .pressure
-                        .expect("Could not unpack pressure!")
-                ),
-                false => println!(
-                    "{}hPa",
-                    self.clone()
-                        .main
-                        .expect("Could not unpack main!")
-                        .pressure
-                        .expect("Could not unpack pressure!")
-                ),
-            },
-            "humidity" => match args.verbose {
-                true => println!(
-                    "Humidity: {}%",
-                    self.clone()
-                        .main
-                        .expect("Could not unpack main!")
-                        .humidity
-                        .expect("Could not unpack humidity!")
-                ),
-                false => println!(
-                    "{}%",
-                    self.clone()
-                        .main
-                        .expect("Could not unpack main!")
-                        .humidity
-                        .expect("Could not unpack humidity!")
-                ),
-            },
-            "visibility" => match args.verbose {
-                true => println!(
-                    "Visibility: {}m",
-                    self.clone()
-                        .visibility
-                        .expect("Could not unpack visibility!")
-                ),
-                false => println!(
-                    "{}m",
-                    self.clone()
-                        .visibility
-                        .expect("Could not unpack visibility!")
-                ),
-            },
-            "wind_speed" => match args.verbose {
-                true => {
-                    let speed = self
-                        .clone()
-                        .wind
-                        .expect("Could not unpack wind!")
-                        .speed
-                        .expect("Could not unpack wind speed!");
-
-                    let speed = match units {
-                        "M" => speed,
-                        "I" => to_mph(speed),
-                        _ => speed,
-                    };
-
-                    let units = match units {
-                        "M" => "m/s",
-                        "I" => "mph",
-                        _ => "m/s",
-                    };
-
-                    println!("Wind Speed: {:.2}{}", speed, units);
-                }
-                false => {
-                    let speed = self
-                        .clone()
-                        .wind
-                        .expect("Could not unpack wind!")
-                        .speed
-                        .expect("Could not unpack wind speed!");
-
-                    let speed = match units {
-                        "M" => speed,
-                        "I" => to_mph(speed),
-                        _ => speed,
-                    };
-
-                    let units = match units {
-                        "M" => "m/s",
-                        "I" => "mph",
-                        _ => "m/s",
-                    };
-
-                    println!("{:.2}{}", speed, units);
-                }
-            },
-            "wind_dir" => match args.verbose {
-                true => println!(
-                    "Wind Direction: {}°",
-                    self.clone()
-                        .wind
-                        .expect("Could not unpack wind!")
-                        .deg
-                        .expect("Could not unpack wind direction!")
-                ),
-                false => println!(
-                    "{}°",
-                    self.clone()
-                        .wind
-                        .expect("Could not unpack wind!")
-                        .deg
-                        .expect("Could not unpack wind direction!")
-                ),
-            },
-            "wind_gust" => match args.verbose {
-                true => {
-                    let speed = self
-                        .clone()
-                        .wind
-                        .expect("Could not unpack wind!")
-                        .gust
-                        .expect("Could not unpack wind gust!");
-
-                    let speed = match units {
-                        "M" => speed,
-                        "I" => to_mph(speed),
-                        _ => speed,
-                    };
-
-                    let units = match units {
-                        "M" => "m/s",
-                        "I" => "mph",
-                        _ => "m/s",
-                    };
-
-                    println!("Wind Gust: {:.2}{}", speed, units);
-                }
-                false => {
-                    let speed = self
-                        .clone()
-                        .wind
-                        .expect("Could not unpack wind!")
-                        .gust
-                        .expect("Could not unpack wind gust!");
-
-                    let speed = match units {
-                        "M" => speed,
-                        "I" => to_mph(speed),
-                        _ => speed,
-                    };
-
-                    let units = match units {
-                        "M" => "m/s",
-                        "I" => "mph",
-                        _ => "m/s",
-                    };
-
-                    println!("{:.2}{}", speed, units);
-                }
-            },
-            "rain_1h" => match args.verbose {
-                true => {
-                    let rain = self
-                        .clone()
-                        .rain
-                        .expect("Could not unpack rain!")
-                        ._1h
-                        .expect("Could not unpack 1 hour rainfall!");
-
-                    let rain = match units {
-                        "M" => rain,
-                        "I" => to_inches(rain),
-                        _ => rain,
-                    };
-
-                    let units = match units {
-                        "M" => "mm",
-                        "I" => "in",
-                        _ => "mm",
-                    };
-
-                    println!("Rainfall 1hr: {:.2}{}", rain, units);
-                }
-                false => {
-                    let rain = self
-                        .clone()
-                        .rain
-                        .expect("Could not unpack rain!")
-                        ._1h
-                        .expect("Could not unpack 1 hour rainfall!");
-
-                    let rain = match units {
-                        "M" => rain,
-                        "I" => to_inches(rain),
-                        _ => rain,
-                    };
-
-                    let units = match units {
-                        "M" => "mm",
-                        "I" => "in",
-                        _ => "mm",
-                    };
-
-                    println!("{:.2}{}", rain, units);
-                }
-            },
-            "rain_3h" => match args.verbose {
-                true => {
-                    let rain = self
-                        .clone()
-                        .rain
-                        .expect("Could not unpack rain!")
-                        ._3h
-                        .expect("Could not unpack 3 hour rainfall!");
-
-                    let rain = match units {
-                        "M" => rain,
-                        "I" => to_inches(rain),
-                        _ => rain,
-                    };
-
-                    let units = match units {
-                        "M" => "mm",
-                        "I" => "in",
-                        _ => "mm",
-                    };
-
-                    println!("Rainfall 3hr: {:.2}{}", rain, units);
-                }
-                false => {
-                    let rain = self
-                        .clone()
-                        .rain
-                        .expect("Could not unpack rain!")
-                        ._3h
-                        .expect("Could not unpack 3 hour rainfall!");
-
-                    let rain = match units {
-                        "M" => rain,
-                        "I" => to_inches(rain),
-                        _ => rain,
-                    };
-
-                    let units = match units {
-                        "M" => "mm",
-                        "I" => "in",
-                        _ => "mm",
-                    };
-
-                    println!("{:.2}{}", rain, units);
-                }
-            },
-            "snow_1h" => match args.verbose {
-                true => {
-                    let snow = self
-                        .clone()
-                        .snow
-                        .expect("Could not unpack snow!")
-                        ._1h
-                        .expect("Could not unpack 1 hour snowfall!");
-
-                    let snow = match units {
-                        "M" => snow,
-                        "I" => to_inches(snow),
-                        _ => snow,
-                    };
-
-                    let units = match units {
-                        "M" => "mm",
-                        "I" => "in",
-                        _ => "mm",
-                    };
-
-                    println!("Snowfall 1hr: {:.2}{}", snow, units);
-                }
-                false => {
-                    let snow = self
-                        .clone()
-                        .snow
-                        .expect("Could not unpack snow!")
-                        ._1h
-                        .expect("Could not unpack 1 hour snowfall!");
-
-                    let snow = match units {
-                        "M" => snow,
-                        "I" => to_inches(snow),
-                        _ => snow,
-                    };
-
-                    let units = match units {
-                        "M" => "mm",
-                        "I" => "in",
-                        _ => "mm",
-                    };
-
-                    println!("{:.2}{}", snow, units);
-                }
-            },
-            "snow_3h" => match args.verbose {
-                true => {
-                    let snow = self
-                        .clone()
-                        .snow
-                        .expect("Could not unpack snow!")
-                        ._3h
-                        .expect("Could not unpack 3 hour snowfall!");
-
-                    let snow = match units {
-                        "M" => snow,
-                        "I" => to_inches(snow),
-                        _ => snow,
-                    };
-
-                    let units = match units {
-                        "M" => "mm",
-                        "I" => "in",
-                        _ => "mm",
-                    };
-
-                    println!("Snowfall 3hr: {:.2}{}", snow, units);
+    fn single(&self) -> bool {
+        true
+    }
+
+    fn template_records(&self, args: &Args, config: &Config) -> Vec<Record> {
+        vec![self.template_record(args, config)]
+    }
+
+    // Human readable report of the current conditions. Lines for data missing
+    // from the response are left out
+    fn summary(&self, summary: Summary, args: &Args, config: &Config) -> String {
+        let settings = Settings::new(args, config);
+        let value = |opt: &str| -> Value {
+            self.field(opt, &settings)
+                .map(|field| field.value)
+                .unwrap_or(Value::Null)
+        };
+
+        let description = self
+            .weather()
+            .and_then(|w| w.description)
+            .map(|description| match (settings.icons, value("glyph")) {
+                (Some(Icons::None) | None, _) | (_, Value::Null) => capitalize(&description),
+                (_, glyph) => format!("{} {}", glyph, capitalize(&description)),
+            });
+        let (temp, feels_like) = (value("temp"), value("feels_like"));
+
+        if summary == Summary::Short {
+            let mut parts = vec![];
+            parts.extend(description);
+            if !temp.is_null() {
+                match feels_like.is_null() {
+                    true => parts.push(temp.to_string()),
+                    false => parts.push(format!("{} (feels like {})", temp, feels_like)),
                 }
-                false => {
-                    let snow = self
-                        .clone()
-                        .snow
-                        .expect("Could not unpack snow!")
-                        ._3h
-                        .expect("Could not unpack 3 hour snowfall!");
-
-                    let snow = match units {
-                        "M" => snow,
-                        "I" => to_inches(snow),
-                        _ => snow,
-                    };
-
-                    let units = match units {
-                        "M" => "mm",
-                        "I" => "in",
-                        _ => "mm",
-                    };
-
-                    println!("{:.2}{}", snow, units);
+            }
+
+            return match self.place(settings.lang.as_deref()) {
+                Some(place) => format!("{}: {}\n", place, parts.join(", ")),
+                None => format!("{}\n", parts.join(", ")),
+            };
+        }
+
+        let mut lines = vec![];
+        lines.extend(self.place(settings.lang.as_deref()));
+        lines.extend(description);
+
+        if !temp.is_null() {
+            match feels_like.is_null() {
+                true => lines.push(format!("Temperature: {}", temp)),
+                false => lines.push(format!("Temperature: {}, feels like {}", temp, feels_like)),
+            }
+        }
+
+        let (temp_min, temp_max) = (value("temp_min"), value("temp_max"));
+        if !temp_min.is_null() && !temp_max.is_null() {
+            lines.push(format!("Min/Max: {} / {}", temp_min, temp_max));
+        }
+
+        for (label, opt) in [("Humidity", "humidity"), ("Pressure", "pressure")] {
+            let value = value(opt);
+            if !value.is_null() {
+                lines.push(format!("{}: {}", label, value));
+            }
+        }
+
+        let (speed, dir, gust) = (value("wind_speed"), value("wind_dir"), value("wind_gust"));
+        if !speed.is_null() {
+            let mut wind = format!("Wind: {}", speed);
+            if !dir.is_null() {
+                wind.push_str(&format!(" from {}", dir));
+            }
+            if !gust.is_null() {
+                wind.push_str(&format!(", gusts {}", gust));
+            }
+            lines.push(wind);
+        }
+
+        for (label, opt) in [("Clouds", "clouds"), ("Visibility", "visibility")] {
+            let value = value(opt);
+            if !value.is_null() {
+                lines.push(format!("{}: {}", label, value));
+            }
+        }
+
+        for (label, opt_1h, opt_3h) in [
+            ("Rain", "rain_1h", "rain_3h"),
+            ("Snow", "snow_1h", "snow_3h"),
+        ] {
+            match (value(opt_1h), value(opt_3h)) {
+                (Value::Null, Value::Null) => (),
+                (Value::Null, value) => {
+                    lines.push(format!("{}: {} in the last 3 hours", label, value))
                 }
-            },
-            "clouds" => match args.verbose {
-                true => println!(
-                    "Clouds: {}%",
-                    self.clone()
-                        .clouds
-                        .expect("Could not unpack clouds!")
-                        .all
-                        .expect("Could not unpack all clouds!")
-                ),
-                false => println!(
-                    "{}%",
-                    self.clone()
-                        .clouds
-                        .expect("Could not unpack clouds!")
-                        .all
-                        .expect("Could not unpack all clouds!")
-                ),
-            },
-            _ => println!("No data to print for option {}", opt),
-        };
+                (value, _) => lines.push(format!("{}: {} in the last hour", label, value)),
+            }
+        }
+
+        for (label, opt) in [("Sunrise", "sunrise"), ("Sunset", "sunset")] {
+            let value = value(opt);
+            if !value.is_null() {
+                lines.push(format!("{}: {}", label, value));
+            }
+        }
+
+        lines.iter().map(|line| format!("{}\n", line)).collect()
     }
 }
@@ -1,5 +1,55 @@
-use super::current_weather::CurrentWeather;
+use super::{
+    air_pollution::AirPollution, astro::Astro, current_weather::CurrentWeather,
+    five_day_forecast::FiveDayForecast, geocoding::Reverse, record::Record,
+};
+use crate::options::{args::Args, config::Config, format::Summary};
 
+#[allow(clippy::large_enum_variant)]
 pub enum Data {
     CurrentWeather(CurrentWeather),
+    FiveDayForecast(FiveDayForecast),
+    Astro(Astro),
+    AirPollution(AirPollution),
+    Reverse(Reverse),
+}
+
+impl Data {
+    pub fn dataset(&self) -> &dyn Dataset {
+        match self {
+            Data::CurrentWeather(dtype) => dtype,
+            Data::FiveDayForecast(dtype) => dtype,
+            Data::Astro(dtype) => dtype,
+            Data::AirPollution(dtype) => dtype,
+            Data::Reverse(dtype) => dtype,
+        }
+    }
+}
+
+// How each kind of data is shown by --print, --format, --template, --output
+// and --summary
+pub trait Dataset {
+    // The requested options, or every option when none are given, with one
+    // record per reading
+    fn records(&self, opts: &[&str], args: &Args, config: &Config) -> Vec<Record>;
+
+    // Whether structured formats show the only record as an object rather
+    // than a list
+    fn single(&self) -> bool {
+        false
+    }
+
+    // Every print option plus values derived from them, for use in templates
+    fn template_records(&self, args: &Args, config: &Config) -> Vec<Record> {
+        self.records(&[], args, config)
+    }
+
+    fn print(&self, opt: &str, args: &Args, config: &Config) {
+        for record in self.records(&[opt], args, config) {
+            if let Some(field) = record.fields.last() {
+                println!("{}", field.text(args.verbose));
+            }
+        }
+    }
+
+    fn summary(&self, summary: Summary, args: &Args, config: &Config) -> String;
 }
@@ -0,0 +1,143 @@
+use crate::options::units::Units;
+
+use super::record::Value;
+
+// Comfort metrics computed from temperature (°C), relative humidity (%) and
+// wind speed (m/s). Each returns None outside the conditions it is defined for
+
+// Magnus formula, with the Sonntag (1990) constants
+pub fn dew_point(temp: f32, humidity: f32) -> Option<f32> {
+    const A: f32 = 17.62;
+    const B: f32 = 243.12;
+
+    if humidity <= 0.0 {
+        return None;
+    }
+
+    let gamma = (humidity / 100.0).ln() + (A * temp) / (B + temp);
+    Some(B * gamma / (A - gamma))
+}
+
+// NWS heat index: the Steadman approximation, switching to the Rothfusz
+// regression and its adjustments once that reaches 80°F. Computed in °F
+pub fn heat_index(temp: f32, humidity: f32) -> f32 {
+    let (t, rh) = (temp * 9.0 / 5.0 + 32.0, humidity);
+
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    let index = match (simple + t) / 2.0 < 80.0 {
+        true => simple,
+        false => {
+            let index = -42.379 + 2.049_015_3 * t + 10.143_331 * rh
+                - 0.224_755_4 * t * rh
+                - 0.006_837_83 * t * t
+                - 0.054_817_17 * rh * rh
+                + 0.001_228_74 * t * t * rh
+                + 0.000_852_82 * t * rh * rh
+                - 0.000_001_99 * t * t * rh * rh;
+
+            if rh < 13.0 && (80.0..=112.0).contains(&t) {
+                index - ((13.0 - rh) / 4.0) * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt()
+            } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+                index + ((rh - 85.0) / 10.0) * ((87.0 - t) / 5.0)
+            } else {
+                index
+            }
+        }
+    };
+
+    (index - 32.0) * 5.0 / 9.0
+}
+
+// Joint NWS / Environment Canada index (2001), defined at or below 10°C with
+// wind of at least 4.8km/h
+pub fn wind_chill(temp: f32, speed: f32) -> Option<f32> {
+    let kmh = speed * 3.6;
+
+    match temp <= 10.0 && kmh >= 4.8 {
+        true => {
+            let v = kmh.powf(0.16);
+            Some(13.12 + 0.6215 * temp - 11.37 * v + 0.3965 * temp * v)
+        }
+        false => None,
+    }
+}
+
+// Environment Canada humidex, from the temperature and dew point
+pub fn humidex(temp: f32, dew_point: f32) -> f32 {
+    let vapour_pressure = 6.11 * (5417.753 * (1.0 / 273.16 - 1.0 / (273.15 + dew_point))).exp();
+    temp + 0.5555 * (vapour_pressure - 10.0)
+}
+
+// Grams of water vapour per cubic metre of air
+pub fn absolute_humidity(temp: f32, humidity: f32) -> f32 {
+    let saturation = 6.112 * ((17.67 * temp) / (temp + 243.5)).exp();
+    saturation * humidity * 2.1674 / (273.15 + temp)
+}
+
+// Stull (2011), valid for humidity between 5% and 99% at sea level pressure
+pub fn wet_bulb(temp: f32, humidity: f32) -> f32 {
+    let (t, rh) = (temp, humidity);
+
+    t * (0.151_977 * (rh + 8.313_659).sqrt()).atan() + (t + rh).atan() - (rh - 1.676_331).atan()
+        + 0.003_918_38 * rh.powf(1.5) * (0.023_101 * rh).atan()
+        - 4.686_035
+}
+
+pub fn label(opt: &str) -> Option<&'static str> {
+    match opt {
+        "dew_point" => Some("Dew Point"),
+        "heat_index" => Some("Heat Index"),
+        "wind_chill" => Some("Wind Chill Index"),
+        "humidex" => Some("Humidex"),
+        "absolute_humidity" => Some("Absolute Humidity"),
+        "wet_bulb" => Some("Wet Bulb Temperature"),
+        _ => None,
+    }
+}
+
+// The inputs of the derived metrics, taken from a single observation or
+// forecast slot and converted from the API's units
+#[derive(Clone, Copy, Debug)]
+pub struct Conditions {
+    pub temp: Option<f32>,
+    pub humidity: Option<f32>,
+    pub wind_speed: Option<f32>,
+}
+
+impl Conditions {
+    pub fn new(
+        temp: Option<f32>,
+        humidity: Option<i32>,
+        wind_speed: Option<f32>,
+        units: &Units,
+    ) -> Self {
+        Self {
+            temp: temp.map(|temp| units.celsius(temp)),
+            humidity: humidity.map(|humidity| humidity as f32),
+            wind_speed: wind_speed.map(|speed| units.metres_per_second(speed)),
+        }
+    }
+
+    // The metric in °C, or g/m³ for absolute humidity
+    pub fn metric(&self, opt: &str) -> Option<f32> {
+        let (temp, humidity) = (self.temp?, self.humidity);
+
+        match opt {
+            "dew_point" => dew_point(temp, humidity?),
+            "heat_index" => Some(heat_index(temp, humidity?)),
+            "wind_chill" => wind_chill(temp, self.wind_speed?),
+            "humidex" => dew_point(temp, humidity?).map(|dew_point| humidex(temp, dew_point)),
+            "absolute_humidity" => Some(absolute_humidity(temp, humidity?)),
+            "wet_bulb" => Some(wet_bulb(temp, humidity?)),
+            _ => None,
+        }
+    }
+}
+
+// A derived metric in the display units
+pub fn value(opt: &str, metric: f32, units: &Units) -> Value {
+    match opt {
+        "absolute_humidity" => Value::number(format!("{:.2}", metric), "g/m³"),
+        _ => Value::celsius(metric, units),
+    }
+}
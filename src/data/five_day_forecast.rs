@@ -1,280 +1,641 @@
 use itertools::Itertools;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    client::{client::OpenWeatherClient, error::Error},
     data::{
-        convert::{to_celsius, to_fahrenheight},
-        data::Data,
+        convert::{place, to_compass},
+        data::{Data, Dataset},
+        derived::{self, Conditions},
+        icons::{glyph, is_night},
+        record::{capitalize, Field, Record, Value},
     },
     options::{
         args::Args,
-        environment::Environment,
-        options::{
-            get_city, get_country, get_key, get_lat, get_lon, get_state, get_units, get_zip,
-        },
+        config::Config,
+        format::{Icons, Summary, WindDir},
+        options::get_units,
+        settings::Settings,
     },
 };
 
 use super::geocoding::Geocoding;
 
-#[allow(dead_code)]
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Main {
-    temp: Option<f32>,
-    feels_like: Option<f32>,
-    temp_min: Option<f32>,
-    temp_max: Option<f32>,
-    pressure: Option<i32>,
-    sea_level: Option<i32>,
-    grnd_level: Option<i32>,
-    humidity: Option<i32>,
-    temp_kf: Option<f32>,
+    pub temp: Option<f32>,
+    pub feels_like: Option<f32>,
+    pub temp_min: Option<f32>,
+    pub temp_max: Option<f32>,
+    pub pressure: Option<i32>,
+    pub sea_level: Option<i32>,
+    pub grnd_level: Option<i32>,
+    pub humidity: Option<i32>,
+    pub temp_kf: Option<f32>,
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Weather {
-    id: Option<i32>,
-    main: Option<String>,
-    description: Option<String>,
-    icon: Option<String>,
+    pub id: Option<i32>,
+    pub main: Option<String>,
+    pub description: Option<String>,
+    pub icon: Option<String>,
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Clouds {
-    all: Option<i8>,
+    pub all: Option<i32>,
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Wind {
-    speed: Option<f32>,
-    deg: Option<i32>,
-    gust: Option<f32>,
+    pub speed: Option<f32>,
+    pub deg: Option<i32>,
+    pub gust: Option<f32>,
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Rain {
-    _1h: Option<f32>,
-    _3h: Option<f32>,
+    #[serde(rename = "3h")]
+    pub _3h: Option<f32>,
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Snow {
-    _1h: Option<f32>,
-    _3h: Option<f32>,
+    #[serde(rename = "3h")]
+    pub _3h: Option<f32>,
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Sys {
-    pod: Option<String>,
+    pub pod: Option<String>,
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Coord {
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct City {
+    pub id: Option<i32>,
+    pub name: Option<String>,
+    pub coord: Option<Coord>,
+    pub country: Option<String>,
+    pub population: Option<i64>,
+    pub timezone: Option<i32>,
+    pub sunrise: Option<i64>,
+    pub sunset: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Day {
-    dt: Option<i32>,
-    main: Option<Main>,
-    weather: Vec<Weather>,
-    clouds: Option<Clouds>,
-    wind: Option<Wind>,
-    visibility: Option<i32>,
-    pop: Option<f32>,
-    rain: Option<Rain>,
-    snow: Option<Snow>,
-    sys: Option<Sys>,
-    dt_txt: Option<String>,
+    pub dt: Option<i64>,
+    pub main: Option<Main>,
+    pub weather: Vec<Weather>,
+    pub clouds: Option<Clouds>,
+    pub wind: Option<Wind>,
+    pub visibility: Option<i32>,
+    pub pop: Option<f32>,
+    pub rain: Option<Rain>,
+    pub snow: Option<Snow>,
+    pub sys: Option<Sys>,
+    pub dt_txt: Option<String>,
+}
+
+impl Day {
+    // Start of the slot in the zone selected with --tz, falling back to the
+    // UTC time reported by the API
+    fn time(&self, settings: &Settings, offset: Option<i32>) -> Option<String> {
+        match self.dt {
+            Some(dt) => Some(settings.datetime(dt, offset)),
+            None => self.dt_txt.clone(),
+        }
+    }
+
+    // None for a slot with neither `dt` nor `dt_txt`
+    fn date(&self, settings: &Settings, offset: Option<i32>) -> Option<String> {
+        match (self.dt, &self.dt_txt) {
+            (Some(dt), _) => Some(settings.date(dt, offset)),
+            (None, Some(dt_txt)) => dt_txt.split_whitespace().next().map(str::to_owned),
+            (None, None) => None,
+        }
+    }
+
+    fn weather(&self) -> Option<Weather> {
+        self.weather.first().cloned()
+    }
+
+    // The icon suffix, falling back to the part of day reported by the API
+    fn is_night(&self) -> bool {
+        match self.weather().and_then(|w| w.icon) {
+            Some(icon) => is_night(&icon),
+            None => self.sys.as_ref().and_then(|sys| sys.pod.as_deref()) == Some("n"),
+        }
+    }
+
+    fn conditions(&self, settings: &Settings) -> Conditions {
+        Conditions::new(
+            self.temp(|m| m.temp),
+            self.main.as_ref().and_then(|m| m.humidity),
+            self.wind.as_ref().and_then(|w| w.speed),
+            &settings.units,
+        )
+    }
+
+    fn temp(&self, f: fn(&Main) -> Option<f32>) -> Option<f32> {
+        self.main.as_ref().and_then(f)
+    }
+
+    // A missing rain or snow object means there was no precipitation in the slot
+    fn rain_3h(&self) -> f32 {
+        self.rain.as_ref().and_then(|rain| rain._3h).unwrap_or(0.0)
+    }
+
+    fn snow_3h(&self) -> f32 {
+        self.snow.as_ref().and_then(|snow| snow._3h).unwrap_or(0.0)
+    }
 }
 
-#[allow(dead_code)]
-#[derive(Deserialize, Clone, Debug)]
+// The slots of each day, in order. Slots without a time cannot be placed on a
+// day and are left out
+fn by_date<'a>(
+    list: &'a [Day],
+    settings: &Settings,
+    offset: Option<i32>,
+) -> Vec<(String, Vec<&'a Day>)> {
+    list.iter()
+        .filter_map(|day| Some((day.date(settings, offset)?, day)))
+        .group_by(|(date, _)| date.clone())
+        .into_iter()
+        .map(|(date, days)| (date, days.map(|(_, day)| day).collect()))
+        .collect()
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct FiveDayForecast {
-    cod: Option<String>,
-    message: Option<i32>,
-    cnt: Option<i32>,
-    list: Option<Vec<Day>>,
+    pub cod: Option<String>,
+    pub message: Option<i32>,
+    pub cnt: Option<i32>,
+    pub list: Option<Vec<Day>>,
+    pub city: Option<City>,
 }
 
-impl FiveDayForecast {
-    pub async fn get(args: &Args, environment: &Environment) -> Result<Data, reqwest::Error> {
-        let key = get_key(args, environment);
-        let lat = get_lat(args, environment);
-        let lon = get_lon(args, environment);
-        let city = get_city(args, environment);
-        let state = get_state(args, environment);
-        let country = get_country(args, environment);
-        let zip = get_zip(args, environment);
-
-        let key = match key {
-            Some(key) => key,
-            None => panic!("No API key found!"),
-        };
+// Every print option, in the order used when no --print is given
+pub const OPTIONS: &[&str] = &[
+    "id",
+    "weather",
+    "description",
+    "icon",
+    "temp",
+    "feels_like",
+    "temp_min",
+    "temp_max",
+    "pressure",
+    "humidity",
+    "visibility",
+    "wind_speed",
+    "wind_dir",
+    "wind_gust",
+    "clouds",
+    "pop",
+    "rain_3h",
+    "snow_3h",
+    "glyph",
+    "dew_point",
+    "heat_index",
+    "wind_chill",
+    "humidex",
+    "absolute_humidity",
+    "wet_bulb",
+];
+
+fn label(opt: &str) -> Option<&'static str> {
+    match opt {
+        "id" => Some("Weather ID"),
+        "weather" => Some("Weather"),
+        "description" => Some("Weather description"),
+        "icon" => Some("Weather Icon"),
+        "temp" => Some("Temperature"),
+        "feels_like" => Some("Wind Chill"),
+        "temp_min" => Some("Low Temperature"),
+        "temp_max" => Some("High Temperature"),
+        "pressure" => Some("Pressure"),
+        "humidity" => Some("Humidity"),
+        "visibility" => Some("Visibility"),
+        "wind_speed" => Some("Wind Speed"),
+        "wind_dir" => Some("Wind Direction"),
+        "wind_gust" => Some("Wind Gust"),
+        "clouds" => Some("Clouds"),
+        "pop" => Some("Chance of Precipitation"),
+        "rain_3h" => Some("Rainfall 3hr"),
+        "snow_3h" => Some("Snowfall 3hr"),
+        "glyph" => Some("Weather Glyph"),
+        opt => derived::label(opt),
+    }
+}
 
-        let (lat, lon) = match (lat, lon) {
-            (Some(lat), Some(lon)) => (lat, lon),
-            _ => {
-                let geocoding = Geocoding::get(&key, &city, &state, &country, &zip).await?;
-
-                match geocoding {
-                    Some(data) => match (data.lat, data.lon) {
-                        (Some(lat), Some(lon)) => (lat, lon),
-                        _ => (0.0, 0.0),
-                    },
-                    None => (0.0, 0.0),
+fn slot_value(opt: &str, day: &Day, settings: &Settings) -> Value {
+    let units = &settings.units;
+    let main = day.main.clone();
+    let wind = day.wind.clone();
+
+    match opt {
+        "id" => day
+            .weather()
+            .and_then(|w| w.id)
+            .map(|id| Value::number(id, "")),
+        "weather" => day.weather().and_then(|w| w.main).map(Value::text),
+        "description" => day.weather().and_then(|w| w.description).map(Value::text),
+        "icon" => day.weather().and_then(|w| w.icon).map(Value::text),
+        "temp" => day.temp(|m| m.temp).map(|t| Value::temp(t, units)),
+        "feels_like" => day.temp(|m| m.feels_like).map(|t| Value::temp(t, units)),
+        "temp_min" => day.temp(|m| m.temp_min).map(|t| Value::temp(t, units)),
+        "temp_max" => day.temp(|m| m.temp_max).map(|t| Value::temp(t, units)),
+        "pressure" => main
+            .and_then(|m| m.pressure)
+            .map(|p| Value::pressure(p as f32, units)),
+        "humidity" => main.and_then(|m| m.humidity).map(|h| Value::number(h, "%")),
+        "visibility" => day.visibility.map(|v| Value::distance(v as f32, units)),
+        "wind_speed" => wind.and_then(|w| w.speed).map(|s| Value::speed(s, units)),
+        "wind_dir" => wind.and_then(|w| w.deg).map(|d| settings.wind_dir(d)),
+        "wind_gust" => wind.and_then(|w| w.gust).map(|s| Value::speed(s, units)),
+        "clouds" => day
+            .clouds
+            .clone()
+            .and_then(|c| c.all)
+            .map(|c| Value::number(c, "%")),
+        "pop" => day
+            .pop
+            .map(|pop| Value::number(format!("{:.0}", pop * 100.0), "%")),
+        "rain_3h" => Some(Value::precipitation(day.rain_3h(), units)),
+        "snow_3h" => Some(Value::precipitation(day.snow_3h(), units)),
+        "glyph" => day.weather().and_then(|w| w.id).map(|id| {
+            let icons = settings.icons.unwrap_or_default();
+            Value::text(glyph(id, day.is_night(), icons))
+        }),
+        opt if derived::label(opt).is_some() => day
+            .conditions(settings)
+            .metric(opt)
+            .map(|metric| derived::value(opt, metric, units)),
+        _ => None,
+    }
+    .into()
+}
+
+// Picks the most frequent value, preferring whichever appeared first on ties
+fn most_common(values: Vec<Value>) -> Option<Value> {
+    values
+        .iter()
+        .enumerate()
+        .max_by_key(|(i, value)| {
+            let count = values.iter().filter(|other| other == value).count();
+            (count, std::cmp::Reverse(*i))
+        })
+        .map(|(_, value)| value.to_owned())
+}
+
+fn mean(values: Vec<f32>) -> Option<f32> {
+    match values.is_empty() {
+        true => None,
+        false => Some(values.iter().sum::<f32>() / values.len() as f32),
+    }
+}
+
+fn max(values: Vec<f32>) -> Option<f32> {
+    values.into_iter().reduce(f32::max)
+}
+
+fn min(values: Vec<f32>) -> Option<f32> {
+    values.into_iter().reduce(f32::min)
+}
+
+// Aggregates the slots of a single day. Slots missing the data are skipped,
+// and the value is Value::Null only if every slot is missing it
+fn daily_value(opt: &str, days: &[&Day], settings: &Settings) -> Value {
+    let units = &settings.units;
+    let collect = |f: &dyn Fn(&Day) -> Option<f32>| -> Vec<f32> {
+        days.iter().filter_map(|day| f(day)).collect()
+    };
+    let temp = |t: f32| Value::temp(t, units);
+
+    match opt {
+        "id" | "weather" | "description" | "icon" => most_common(
+            days.iter()
+                .map(|day| slot_value(opt, day, settings))
+                .filter(|value| *value != Value::Null)
+                .collect(),
+        ),
+        "temp" => {
+            match (
+                max(collect(&|day| day.temp(|m| m.temp_max))),
+                min(collect(&|day| day.temp(|m| m.temp_min))),
+            ) {
+                (Some(high), Some(low)) => Some(Value::range(temp(high), temp(low))),
+                _ => None,
+            }
+        }
+        "feels_like" => {
+            let feels_like = collect(&|day| day.temp(|m| m.feels_like));
+            match (max(feels_like.clone()), min(feels_like)) {
+                (Some(high), Some(low)) => Some(Value::range(temp(high), temp(low))),
+                _ => None,
+            }
+        }
+        "temp_min" => min(collect(&|day| day.temp(|m| m.temp_min))).map(temp),
+        "temp_max" => max(collect(&|day| day.temp(|m| m.temp_max))).map(temp),
+        "pressure" => mean(collect(&|day| {
+            day.main.as_ref().and_then(|m| m.pressure).map(|p| p as f32)
+        }))
+        .map(|p| Value::pressure(p, units)),
+        "humidity" => mean(collect(&|day| {
+            day.main.as_ref().and_then(|m| m.humidity).map(|h| h as f32)
+        }))
+        .map(|h| Value::number(format!("{:.0}", h), "%")),
+        "visibility" => mean(collect(&|day| day.visibility.map(|v| v as f32)))
+            .map(|v| Value::distance(v, units)),
+        "wind_speed" => max(collect(&|day| day.wind.as_ref().and_then(|w| w.speed)))
+            .map(|s| Value::speed(s, units)),
+        "wind_gust" => max(collect(&|day| day.wind.as_ref().and_then(|w| w.gust)))
+            .map(|s| Value::speed(s, units)),
+        "wind_dir" => {
+            // Average the directions as unit vectors so that 350° and 10° give 0°
+            let rads = collect(&|day| {
+                day.wind
+                    .as_ref()
+                    .and_then(|w| w.deg)
+                    .map(|d| (d as f32).to_radians())
+            });
+            match rads.is_empty() {
+                true => None,
+                false => {
+                    let (x, y) = rads
+                        .iter()
+                        .fold((0.0, 0.0), |(x, y), rad| (x + rad.sin(), y + rad.cos()));
+                    let deg = (f32::atan2(x, y).to_degrees().round() as i32).rem_euclid(360);
+                    Some(settings.wind_dir(deg))
                 }
             }
+        }
+        "clouds" => mean(collect(&|day| {
+            day.clouds.as_ref().and_then(|c| c.all).map(|c| c as f32)
+        }))
+        .map(|c| Value::number(format!("{:.0}", c), "%")),
+        "pop" => max(collect(&|day| day.pop))
+            .map(|pop| Value::number(format!("{:.0}", pop * 100.0), "%")),
+        "rain_3h" => Some(Value::precipitation(
+            days.iter().map(|day| day.rain_3h()).sum(),
+            units,
+        )),
+        "snow_3h" => Some(Value::precipitation(
+            days.iter().map(|day| day.snow_3h()).sum(),
+            units,
+        )),
+        // A day's glyph always uses the day variant of its most common condition
+        "glyph" => match daily_value("id", days, settings) {
+            Value::Number { value, .. } => value.parse().ok(),
+            _ => None,
+        }
+        .map(|id| Value::text(glyph(id, false, settings.icons.unwrap_or_default()))),
+        // The extremes of the day for the indices, the mean otherwise
+        opt if derived::label(opt).is_some() => {
+            let metrics = days
+                .iter()
+                .filter_map(|day| day.conditions(settings).metric(opt))
+                .collect();
+
+            match opt {
+                "heat_index" | "humidex" => max(metrics),
+                "wind_chill" => min(metrics),
+                _ => mean(metrics),
+            }
+            .map(|metric| derived::value(opt, metric, units))
+        }
+        _ => None,
+    }
+    .into()
+}
+
+impl FiveDayForecast {
+    pub async fn get(
+        client: &OpenWeatherClient,
+        args: &Args,
+        config: &Config,
+    ) -> Result<Data, Error> {
+        let (lat, lon) = Geocoding::locate(client, args, config).await?;
+
+        let units = get_units(args, config).unwrap_or_default();
+
+        let query = [
+            ("lat", lat.to_string()),
+            ("lon", lon.to_string()),
+            ("units", units.query().to_string()),
+        ];
+        let data: FiveDayForecast = client.get("/data/2.5/forecast", &query).await?;
+
+        Ok(Data::FiveDayForecast(data))
+    }
+
+    fn records_with(&self, opts: &[&str], daily: bool, settings: &Settings) -> Vec<Record> {
+        let offset = self.timezone();
+        let opts = match opts.is_empty() {
+            true => OPTIONS,
+            false => opts,
         };
 
-        let req_uri = format!(
-            "https://api.openweathermap.org/data/2.5/forecast?lat={}&lon={}&appid={}",
-            lat, lon, key
-        );
+        let field = |opt: &str, value: Value| Field::new(opt, label(opt).unwrap_or(opt), value);
 
-        let response = reqwest::get(req_uri).await?;
+        let list = self.list.clone().unwrap_or_default();
 
-        if !response.status().is_success() {
-            println!("Response failed with status code {}", response.status());
-            panic!("Failed to get response, aborting!")
+        match daily {
+            true => by_date(&list, settings, offset)
+                .into_iter()
+                .map(|(date, days)| {
+                    let mut record = Record::default();
+                    record.push(Field::new("date", "Date", Value::text(date)));
+                    for opt in opts {
+                        record.push(field(opt, daily_value(opt, &days, settings)));
+                    }
+                    record
+                })
+                .collect(),
+            false => list
+                .iter()
+                .map(|day| {
+                    let mut record = Record::default();
+                    record.push(Field::new(
+                        "time",
+                        "Time",
+                        day.time(settings, offset).map(Value::text).into(),
+                    ));
+                    for opt in opts {
+                        record.push(field(opt, slot_value(opt, day, settings)));
+                    }
+                    record
+                })
+                .collect(),
         }
+    }
 
-        let body = response.text().await?;
-        let data: FiveDayForecast =
-            serde_json::from_str(&body).expect("Failed to deserialize response body!");
+    fn timezone(&self) -> Option<i32> {
+        self.city.as_ref().and_then(|city| city.timezone)
+    }
 
-        Ok(Data::FiveDayForecast(data))
+    fn place(&self) -> Option<String> {
+        let city = self.city.clone()?;
+        place(city.name, city.country)
+    }
+}
+
+impl Dataset for FiveDayForecast {
+    // One record per 3 hour slot, or per day with --daily, each led by the
+    // time or date it covers
+    fn records(&self, opts: &[&str], args: &Args, config: &Config) -> Vec<Record> {
+        self.records_with(opts, args.daily, &Settings::new(args, config))
     }
 
-    pub fn print(&self, opt: &str, args: &Args, environment: &Environment) {
-        let units = get_units(args, environment);
+    // Every print option of each record plus values derived from them, for
+    // use in templates
+    fn template_records(&self, args: &Args, config: &Config) -> Vec<Record> {
+        let city = self.city.clone();
+        let name = city.clone().and_then(|city| city.name);
+        let country = city.and_then(|city| city.country);
+
+        // The compass point is derived from the direction in degrees, whatever
+        // --wind-dir-format shows
+        let settings = Settings {
+            wind_dir: WindDir::Deg,
+            ..Settings::new(args, config)
+        };
+        let degs = self
+            .records_with(&["wind_dir"], args.daily, &settings)
+            .into_iter()
+            .map(
+                |record| match record.fields.last().map(|field| &field.value) {
+                    Some(Value::Number { value, .. }) => value.parse::<f32>().ok(),
+                    _ => None,
+                },
+            );
+
+        self.records(&[], args, config)
+            .into_iter()
+            .zip(degs)
+            .map(|(mut record, deg)| {
+                record.push(Field::new(
+                    "name",
+                    "Name",
+                    name.clone().map(Value::text).into(),
+                ));
+                record.push(Field::new(
+                    "country",
+                    "Country",
+                    country.clone().map(Value::text).into(),
+                ));
+                record.push(Field::new(
+                    "place",
+                    "Place",
+                    self.place().map(Value::text).into(),
+                ));
+                record.push(Field::new(
+                    "wind_dir_compass",
+                    "Wind Direction",
+                    deg.map(|deg| Value::text(to_compass(deg))).into(),
+                ));
+                record
+            })
+            .collect()
+    }
 
-        let units = match units {
-            Some(units) => units,
-            None => "M".to_string(),
+    fn print(&self, opt: &str, args: &Args, config: &Config) {
+        if label(opt).is_none() {
+            println!("No data to print for option {}", opt);
+            return;
         }
-        .to_uppercase();
-
-        let units = units.as_str();
-
-        let list = self.list.clone().expect("Could not unpack list!");
-
-        let days = list.iter().group_by(|day| {
-            day.dt_txt
-                .clone()
-                .expect("Could not unpack datetime string!")
-                .split_whitespace()
-                .next()
-                .unwrap()
-                .to_owned()
-        });
-
-        match opt {
-            "temp" => match args.verbose {
-                true => {
-                    println!("Five day forecast:");
-
-                    // TODO: consider pulling this procedure into its own function
-                    // The temperatures being presented here seem wrong as well
-                    let (date, (tmax, tmin)): (Vec<String>, (Vec<f32>, Vec<f32>)) = days
-                        .into_iter()
-                        .map(|(_, day)| {
-                            let day = day.collect::<Vec<&Day>>();
-                            day.iter()
-                                .map(|day| {
-                                    let date = day
-                                        .dt_txt
-                                        .clone()
-                                        .expect("Could not unpack datetime string!")
-                                        .split_whitespace()
-                                        .next()
-                                        .expect("Could not unpack stripped datetime string!")
-                                        .to_owned();
-
-                                    let main = day.main.clone().expect("Could not unpack main!");
-
-                                    let tmax =
-                                        main.temp_max.expect("Could not unpack max temperature!");
-
-                                    let tmin =
-                                        main.temp_min.expect("Could not unpack min temperature!");
-
-                                    (date, tmax, tmin)
-                                })
-                                .fold(None, |acc, (date, x, y)| {
-                                    Some(match acc {
-                                        Some((date, max, min)) => {
-                                            (date, f32::max(max, x), f32::min(min, y))
-                                        }
-                                        None => (date, x, y),
-                                    })
-                                })
-                                .expect("Could not unpack max temperature for day!")
-                        })
-                        .map(|(date, tmax, tmin)| match units {
-                            "M" => (date, (to_celsius(tmax), to_celsius(tmin))),
-                            "I" => (date, (to_fahrenheight(tmax), to_fahrenheight(tmin))),
-                            _ => (date, (tmax, tmin)),
-                        })
-                        .unzip();
-
-                    // let (date, (tmin, tmax)) = get_daily_temps(days, units);
-
-                    for (date, (max, min)) in date.iter().zip(tmax.iter().zip(tmin.iter())) {
-                        println!("[{}] High: {:.2}, Low: {:.2}", date, max, min);
-                    }
+
+        for record in self.records(&[opt], args, config) {
+            let (when, field) = (&record.fields[0], &record.fields[1]);
+            println!("[{}] {}", when.value, field.text(args.verbose));
+        }
+    }
+
+    // Day by day report of the forecast. Parts of a day's report for data
+    // missing from the response are left out
+    fn summary(&self, summary: Summary, args: &Args, config: &Config) -> String {
+        let settings = Settings::new(args, config);
+        let offset = self.timezone();
+        let list = self.list.clone().unwrap_or_default();
+
+        let mut lines = vec![];
+        lines.extend(self.place());
+
+        for (date, days) in by_date(&list, &settings, offset) {
+            let value = |opt: &str| daily_value(opt, &days, &settings);
+
+            let description = match (value("description"), settings.icons, value("glyph")) {
+                (Value::Text(description), Some(Icons::None) | None, _)
+                | (Value::Text(description), _, Value::Null) => Some(capitalize(&description)),
+                (Value::Text(description), _, glyph) => {
+                    Some(format!("{} {}", glyph, capitalize(&description)))
                 }
-                false => {
-                    let (date, (tmax, tmin)): (Vec<String>, (Vec<f32>, Vec<f32>)) = days
-                        .into_iter()
-                        .map(|(_, day)| {
-                            let day = day.collect::<Vec<&Day>>();
-                            day.iter()
-                                .map(|day| {
-                                    let date = day
-                                        .dt_txt
-                                        .clone()
-                                        .expect("Could not unpack datetime string!")
-                                        .split_whitespace()
-                                        .next()
-                                        .expect("Could not unpack stripped datetime string!")
-                                        .to_owned();
-
-                                    let main = day.main.clone().expect("Could not unpack main!");
-
-                                    let tmax =
-                                        main.temp_max.expect("Could not unpack max temperature!");
-
-                                    let tmin =
-                                        main.temp_min.expect("Could not unpack min temperature!");
-
-                                    (date, tmax, tmin)
-                                })
-                                .fold(None, |acc, (date, x, y)| {
-                                    Some(match acc {
-                                        Some((date, max, min)) => {
-                                            (date, f32::max(max, x), f32::min(min, y))
-                                        }
-                                        None => (date, x, y),
-                                    })
-                                })
-                                .expect("Could not unpack max temperature for day!")
-                        })
-                        .map(|(date, tmax, tmin)| match units {
-                            "M" => (date, (to_celsius(tmax), to_celsius(tmin))),
-                            "I" => (date, (to_fahrenheight(tmax), to_fahrenheight(tmin))),
-                            _ => (date, (tmax, tmin)),
-                        })
-                        .unzip();
-
-                    for (date, (max, min)) in date.iter().zip(tmax.iter().zip(tmin.iter())) {
-                        println!("[{}] {:.2}, {:.2}", date, max, min);
+                _ => None,
+            };
+
+            let temp = match value("temp") {
+                Value::Range { high, low } => Some(format!("{} / {}", high, low)),
+                _ => None,
+            };
+
+            let pop = value("pop");
+            let rain: f32 = days.iter().map(|day| day.rain_3h()).sum();
+            let snow: f32 = days.iter().map(|day| day.snow_3h()).sum();
+
+            if summary == Summary::Short {
+                let mut parts = vec![];
+                parts.extend(description);
+                parts.extend(temp);
+                if !pop.is_null() {
+                    parts.push(format!("{} chance of precipitation", pop));
+                }
+
+                lines.push(format!("[{}] {}", date, parts.join(", ")));
+                continue;
+            }
+
+            lines.push(match description {
+                Some(description) => format!("[{}] {}", date, description),
+                None => format!("[{}]", date),
+            });
+
+            if let Some(temp) = temp {
+                lines.push(format!("  High/Low: {}", temp));
+            }
+
+            let humidity = value("humidity");
+            if !humidity.is_null() {
+                lines.push(format!("  Humidity: {}", humidity));
+            }
+
+            let (speed, gust) = (value("wind_speed"), value("wind_gust"));
+            if !speed.is_null() {
+                match gust.is_null() {
+                    true => lines.push(format!("  Wind: up to {}", speed)),
+                    false => lines.push(format!("  Wind: up to {}, gusts {}", speed, gust)),
+                }
+            }
+
+            if !pop.is_null() {
+                let mut precipitation = format!("  Precipitation: {} chance", pop);
+                for (label, amount) in [("rain", rain), ("snow", snow)] {
+                    if amount > 0.0 {
+                        let amount = Value::precipitation(amount, &settings.units);
+                        precipitation.push_str(&format!(", {} {}", amount, label));
                     }
                 }
-            },
-            _ => println!("No data to print for option {}", opt),
-        };
+                lines.push(precipitation);
+            }
+        }
+
+        lines.iter().map(|line| format!("{}\n", line)).collect()
     }
 }
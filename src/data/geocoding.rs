@@ -1,57 +1,76 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    io::{BufRead, IsTerminal},
+    sync::Mutex,
+};
 
 use serde::Deserialize;
 
-async fn geocoding_by_name(
-    key: &str,
-    city: &str,
-    state: &str,
-    country: &str,
-) -> Result<Geocoding, reqwest::Error> {
-    let req_uri = format!(
-        "http://api.openweathermap.org/geo/1.0/direct?q={},{},{}&limit={}&appid={}",
-        city, state, country, 1, key
-    );
+use crate::{
+    client::{client::OpenWeatherClient, error::Error},
+    data::{
+        convert::language,
+        data::{Data, Dataset},
+        record::{Field, Record, Value},
+    },
+    options::{
+        args::Args,
+        config::{save_location, Config, Location},
+        format::Summary,
+        options::{get_city, get_country, get_lang, get_lat, get_lon, get_state, get_zip},
+    },
+};
 
-    let response = reqwest::get(req_uri).await?;
+// Places returned by reverse geocoding, nearest first
+const REVERSE_LIMIT: usize = 5;
 
-    if !response.status().is_success() {
-        println!("Response failed with status code {}", response.status());
-        panic!("Failed to get response, aborting!")
-    }
+// Candidates to choose from when a name matches several places
+const NAME_LIMIT: usize = 5;
+
+// Held while prompting, so that the locations of a batch ask one at a time
+static PROMPT: Mutex<()> = Mutex::new(());
 
-    let body = response.text().await?;
-    let geocoding: Geocoding =
-        serde_json::from_str(&body).expect("Failed to deserialize response body!");
+// State and country narrow the search down, but are optional
+async fn geocoding_by_name(
+    client: &OpenWeatherClient,
+    city: &str,
+    state: Option<&str>,
+    country: Option<&str>,
+) -> Result<Vec<GeocodingData>, Error> {
+    let q: Vec<&str> = [Some(city), state, country].into_iter().flatten().collect();
+    let query = [("q", q.join(",")), ("limit", NAME_LIMIT.to_string())];
 
-    Ok(geocoding)
+    // The direct endpoint responds with a (possibly empty) list of matches
+    client.get("/geo/1.0/direct", &query).await
 }
 
 async fn geocoding_by_zip(
-    key: &str,
+    client: &OpenWeatherClient,
     country: &str,
     zip: &str,
-) -> Result<Geocoding, reqwest::Error> {
-    let req_uri = format!(
-        "http://api.openweathermap.org/geo/1.0/zip?zip={},{}&appid={}",
-        zip, country, key
-    );
+) -> Result<Geocoding, Error> {
+    let query = [("zip", format!("{},{}", zip, country))];
 
-    let response = reqwest::get(req_uri).await?;
+    let geocoding_data: GeocodingData = client.get("/geo/1.0/zip", &query).await?;
 
-    if !response.status().is_success() {
-        println!("Response failed with status code {}", response.status());
-        panic!("Failed to get response, aborting!")
-    }
-
-    let body = response.text().await?;
-    let value: serde_json::Value = serde_json::from_str(&body).unwrap();
-    let geocoding_data: GeocodingData = serde_json::from_value(value).unwrap();
-    let geocoding = Geocoding {
+    Ok(Geocoding {
         data: Some(geocoding_data),
-    };
+    })
+}
+
+async fn geocoding_by_coords(
+    client: &OpenWeatherClient,
+    lat: f64,
+    lon: f64,
+    limit: usize,
+) -> Result<Vec<GeocodingData>, Error> {
+    let query = [
+        ("lat", lat.to_string()),
+        ("lon", lon.to_string()),
+        ("limit", limit.to_string()),
+    ];
 
-    Ok(geocoding)
+    client.get("/geo/1.0/reverse", &query).await
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -65,6 +84,88 @@ pub struct GeocodingData {
     pub zip: Option<String>,
 }
 
+impl GeocodingData {
+    // The name in the given language when OpenWeather knows one, e.g. "de" or
+    // "de_DE", or the English name otherwise
+    pub fn local_name(&self, lang: Option<&str>) -> Option<String> {
+        let local_names = self.local_names.as_ref();
+        lang.and_then(|lang| local_names?.get(&language(lang)).cloned())
+            .or_else(|| self.name.clone())
+    }
+
+    // e.g. "Mountain View, California, US"
+    pub fn place(&self, lang: Option<&str>) -> Option<String> {
+        let parts: Vec<String> = [
+            self.local_name(lang),
+            self.state.clone(),
+            self.country.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect();
+
+        match parts.is_empty() {
+            true => None,
+            false => Some(parts.join(", ")),
+        }
+    }
+
+    // e.g. "Mountain View, California, US (37.3894, -122.0832)"
+    pub fn describe(&self, lang: Option<&str>) -> String {
+        let place = self.place(lang).unwrap_or_default();
+        match (self.lat, self.lon) {
+            (Some(lat), Some(lon)) => format!("{} ({:.4}, {:.4})", place, lat, lon),
+            _ => place,
+        }
+    }
+}
+
+// Asks on the terminal which of `count` listed matches to use, until a valid
+// number or the end of input
+fn prompt(list: &str, count: usize) -> Option<usize> {
+    let _guard = PROMPT.lock().unwrap_or_else(|e| e.into_inner());
+    eprintln!("Several places match:\n{}", list);
+
+    let mut lines = std::io::stdin().lock().lines();
+    loop {
+        eprint!("Pick one [1-{}]: ", count);
+        match lines.next()?.ok()?.trim().parse::<usize>() {
+            Ok(pick) if (1..=count).contains(&pick) => return Some(pick),
+            _ => continue,
+        }
+    }
+}
+
+// Numbered list of matches to choose from
+pub fn list(matches: &[GeocodingData]) -> String {
+    matches
+        .iter()
+        .enumerate()
+        .map(|(n, data)| format!("  {}. {}", n + 1, data.describe(None)))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// One of several matches: the --pick'th, or one picked interactively when
+// stdin is a terminal. Otherwise the matches are listed in the error
+fn choose(mut matches: Vec<GeocodingData>, pick: Option<usize>) -> Result<GeocodingData, Error> {
+    let list = list(&matches);
+    let count = matches.len();
+
+    let pick = match pick {
+        Some(pick) => Some(pick),
+        None if count == 1 => Some(1),
+        None if std::io::stdin().is_terminal() => prompt(&list, count),
+        None => None,
+    };
+
+    match pick {
+        Some(pick) if (1..=count).contains(&pick) => Ok(matches.swap_remove(pick - 1)),
+        _ => Err(Error::AmbiguousLocation(list)),
+    }
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct Geocoding {
     pub data: Option<GeocodingData>,
@@ -72,45 +173,204 @@ pub struct Geocoding {
 
 impl Geocoding {
     pub async fn get(
-        key: &str,
+        client: &OpenWeatherClient,
         city: Option<String>,
         state: Option<String>,
         country: Option<String>,
         zip: Option<String>,
-    ) -> Result<Option<GeocodingData>, reqwest::Error> {
-        match (&city, &state, &country, &zip) {
-            (Some(city), Some(state), Some(country), Some(zip)) => {
-                let geocoding = geocoding_by_name(key, city, state, country).await?;
-
-                match geocoding.data {
-                    Some(data) => Ok(Some(data.to_owned())),
-                    _ => {
-                        let geocoding = geocoding_by_zip(key, country, zip).await?;
-
-                        match geocoding.data {
-                            Some(data) => Ok(Some(data.to_owned())),
-                            _ => Ok(None),
-                        }
-                    }
-                }
-            }
-            (Some(city), Some(state), Some(country), None) => {
-                let geocoding = geocoding_by_name(key, city, state, country).await?;
+        pick: Option<usize>,
+    ) -> Result<Option<GeocodingData>, Error> {
+        if let Some(city) = &city {
+            let matches =
+                geocoding_by_name(client, city, state.as_deref(), country.as_deref()).await?;
 
-                match geocoding.data {
-                    Some(data) => Ok(Some(data.to_owned())),
-                    _ => Ok(None),
-                }
+            if !matches.is_empty() {
+                return choose(matches, pick).map(Some);
             }
-            (_, _, Some(country), Some(zip)) => {
-                let geocoding = geocoding_by_zip(key, country, zip).await?;
+        }
 
-                match geocoding.data {
-                    Some(data) => Ok(Some(data.to_owned())),
-                    _ => Ok(None),
-                }
-            }
+        match (&country, &zip) {
+            (Some(country), Some(zip)) => Ok(geocoding_by_zip(client, country, zip).await?.data),
             _ => Ok(None),
         }
     }
+
+    // Every place matching a name such as "Springfield,IL,US"
+    pub async fn search(
+        client: &OpenWeatherClient,
+        name: &str,
+    ) -> Result<Vec<GeocodingData>, Error> {
+        geocoding_by_name(client, name, None, None).await
+    }
+
+    // Named places near the coordinates, nearest first
+    pub async fn reverse(
+        client: &OpenWeatherClient,
+        lat: f64,
+        lon: f64,
+        limit: usize,
+    ) -> Result<Vec<GeocodingData>, Error> {
+        geocoding_by_coords(client, lat, lon, limit).await
+    }
+
+    // Resolves the coordinates to query, preferring explicit lat/lon over geocoding
+    pub async fn locate(
+        client: &OpenWeatherClient,
+        args: &Args,
+        config: &Config,
+    ) -> Result<(f64, f64), Error> {
+        if let (Some(lat), Some(lon)) = (get_lat(args, config), get_lon(args, config)) {
+            return Ok((lat, lon));
+        }
+
+        let geocoding = Self::get(
+            client,
+            get_city(args, config),
+            get_state(args, config),
+            get_country(args, config),
+            get_zip(args, config),
+            args.pick,
+        )
+        .await?;
+
+        let (data, lat, lon) = match geocoding {
+            Some(data) => match (data.lat, data.lon) {
+                (Some(lat), Some(lon)) => (data, lat, lon),
+                _ => return Err(Error::LocationNotFound),
+            },
+            None => return Err(Error::LocationNotFound),
+        };
+
+        if let Some(name) = &args.save_location {
+            let location = Location {
+                lat: Some(lat),
+                lon: Some(lon),
+                name: data.name.clone(),
+                state: data.state.clone(),
+                country: data.country.clone(),
+                ..Location::default()
+            };
+            let path = save_location(config, name, &location, false)?;
+            eprintln!(
+                "Saved {} as {} in {}",
+                data.describe(None),
+                name,
+                path.display()
+            );
+        }
+
+        Ok((lat, lon))
+    }
+}
+
+// Every print option of --api reverse, in the order used when no --print is given
+pub const OPTIONS: &[&str] = &[
+    "place",
+    "name",
+    "state",
+    "country",
+    "lat",
+    "lon",
+    "local_names",
+];
+
+pub fn label(opt: &str) -> Option<&'static str> {
+    match opt {
+        "place" => Some("Place"),
+        "name" => Some("Name"),
+        "state" => Some("State"),
+        "country" => Some("Country"),
+        "lat" => Some("Latitude"),
+        "lon" => Some("Longitude"),
+        "local_names" => Some("Local Names"),
+        _ => None,
+    }
+}
+
+// Places near the location, for --api reverse
+#[derive(Clone, Debug)]
+pub struct Reverse {
+    pub places: Vec<GeocodingData>,
+}
+
+impl Reverse {
+    pub async fn get(
+        client: &OpenWeatherClient,
+        args: &Args,
+        config: &Config,
+    ) -> Result<Data, Error> {
+        let (lat, lon) = Geocoding::locate(client, args, config).await?;
+        let places = Geocoding::reverse(client, lat, lon, REVERSE_LIMIT).await?;
+
+        Ok(Data::Reverse(Reverse { places }))
+    }
+
+    fn value(place: &GeocodingData, opt: &str, lang: Option<&str>) -> Option<Value> {
+        match opt {
+            "place" => place.place(lang).map(Value::text),
+            "name" => place.local_name(lang).map(Value::text),
+            "state" => place.state.clone().map(Value::text),
+            "country" => place.country.clone().map(Value::text),
+            "lat" => place.lat.map(|lat| Value::number(lat, "")),
+            "lon" => place.lon.map(|lon| Value::number(lon, "")),
+            // e.g. "de: München, en: Munich", sorted by language
+            "local_names" => place.local_names.as_ref().map(|names| {
+                let mut names: Vec<String> = names
+                    .iter()
+                    .map(|(lang, name)| format!("{}: {}", lang, name))
+                    .collect();
+                names.sort();
+                Value::text(names.join(", "))
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Dataset for Reverse {
+    // One record per place
+    fn records(&self, opts: &[&str], args: &Args, config: &Config) -> Vec<Record> {
+        let lang = get_lang(args, config);
+        let opts = match opts.is_empty() {
+            true => OPTIONS,
+            false => opts,
+        };
+
+        self.places
+            .iter()
+            .map(|place| {
+                let mut record = Record::default();
+                for opt in opts {
+                    record.push(Field::new(
+                        opt,
+                        label(opt).unwrap_or(opt),
+                        Self::value(place, opt, lang.as_deref()).into(),
+                    ));
+                }
+                record
+            })
+            .collect()
+    }
+
+    fn print(&self, opt: &str, args: &Args, config: &Config) {
+        if label(opt).is_none() || self.places.is_empty() {
+            println!("No data to print for option {}", opt);
+            return;
+        }
+
+        for (n, record) in self.records(&[opt], args, config).iter().enumerate() {
+            println!("[{}] {}", n + 1, record.fields[0].text(args.verbose));
+        }
+    }
+
+    // Numbered list of the places with their coordinates
+    fn summary(&self, _summary: Summary, args: &Args, config: &Config) -> String {
+        let lang = get_lang(args, config);
+
+        self.places
+            .iter()
+            .enumerate()
+            .map(|(n, place)| format!("{}. {}\n", n + 1, place.describe(lang.as_deref())))
+            .collect()
+    }
 }
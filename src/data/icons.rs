@@ -0,0 +1,105 @@
+use crate::options::format::Icons;
+
+// Day and night glyphs for a condition
+struct Glyph {
+    emoji: (&'static str, &'static str),
+    nerd: (&'static str, &'static str),
+    ascii: (&'static str, &'static str),
+}
+
+// Condition codes, see https://openweathermap.org/weather-conditions.
+// Nerd Font glyphs are from the nf-weather set
+fn lookup(id: i32) -> Glyph {
+    match id {
+        200..=299 => Glyph {
+            emoji: ("⛈️", "⛈️"),
+            nerd: ("\u{e30f}", "\u{e32a}"), // day_thunderstorm, night_alt_thunderstorm
+            ascii: ("storm", "storm"),
+        },
+        300..=399 => Glyph {
+            emoji: ("🌦️", "🌧️"),
+            nerd: ("\u{e309}", "\u{e326}"), // day_showers, night_alt_showers
+            ascii: ("drizzle", "drizzle"),
+        },
+        511 | 611..=616 => Glyph {
+            emoji: ("🌨️", "🌨️"),
+            nerd: ("\u{e3ad}", "\u{e3ad}"), // sleet
+            ascii: ("sleet", "sleet"),
+        },
+        500..=599 => Glyph {
+            emoji: ("🌧️", "🌧️"),
+            nerd: ("\u{e308}", "\u{e325}"), // day_rain, night_alt_rain
+            ascii: ("rain", "rain"),
+        },
+        600..=699 => Glyph {
+            emoji: ("❄️", "❄️"),
+            nerd: ("\u{e30a}", "\u{e327}"), // day_snow, night_alt_snow
+            ascii: ("snow", "snow"),
+        },
+        711 => Glyph {
+            emoji: ("💨", "💨"),
+            nerd: ("\u{e35c}", "\u{e35c}"), // smoke
+            ascii: ("smoke", "smoke"),
+        },
+        731 | 751 | 761 | 762 => Glyph {
+            emoji: ("💨", "💨"),
+            nerd: ("\u{e35d}", "\u{e35d}"), // dust
+            ascii: ("dust", "dust"),
+        },
+        771 => Glyph {
+            emoji: ("🌬️", "🌬️"),
+            nerd: ("\u{e34b}", "\u{e34b}"), // strong_wind
+            ascii: ("squall", "squall"),
+        },
+        781 => Glyph {
+            emoji: ("🌪️", "🌪️"),
+            nerd: ("\u{e351}", "\u{e351}"), // tornado
+            ascii: ("tornado", "tornado"),
+        },
+        700..=799 => Glyph {
+            emoji: ("🌫️", "🌫️"),
+            nerd: ("\u{e313}", "\u{e313}"), // fog
+            ascii: ("fog", "fog"),
+        },
+        800 => Glyph {
+            emoji: ("☀️", "🌙"),
+            nerd: ("\u{e30d}", "\u{e32b}"), // day_sunny, night_clear
+            ascii: ("clear", "clear night"),
+        },
+        801 | 802 => Glyph {
+            emoji: ("🌤️", "☁️"),
+            nerd: ("\u{e302}", "\u{e37e}"), // day_cloudy, night_alt_cloudy
+            ascii: ("partly cloudy", "partly cloudy"),
+        },
+        803 | 804 => Glyph {
+            emoji: ("☁️", "☁️"),
+            nerd: ("\u{e312}", "\u{e312}"), // cloudy
+            ascii: ("cloudy", "cloudy"),
+        },
+        _ => Glyph {
+            emoji: ("❔", "❔"),
+            nerd: ("\u{e374}", "\u{e374}"), // na
+            ascii: ("unknown", "unknown"),
+        },
+    }
+}
+
+// OpenWeather icon codes end in "d" during the day and "n" at night
+pub fn is_night(icon: &str) -> bool {
+    icon.ends_with('n')
+}
+
+pub fn glyph(id: i32, night: bool, icons: Icons) -> &'static str {
+    let glyph = lookup(id);
+    let (day, night_glyph) = match icons {
+        Icons::Emoji => glyph.emoji,
+        Icons::Nerd => glyph.nerd,
+        Icons::Ascii => glyph.ascii,
+        Icons::None => ("", ""),
+    };
+
+    match night {
+        true => night_glyph,
+        false => day,
+    }
+}
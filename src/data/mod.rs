@@ -1,4 +1,15 @@
+pub mod air_pollution;
+pub mod aqi;
+pub mod astro;
+pub mod bar;
+pub mod batch;
 pub mod convert;
 pub mod current_weather;
+#[allow(clippy::module_inception)]
 pub mod data;
+pub mod derived;
+pub mod five_day_forecast;
 pub mod geocoding;
+pub mod icons;
+pub mod record;
+pub mod template;
@@ -0,0 +1,229 @@
+use std::fmt;
+
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+use crate::options::{format::Format, units::Units};
+
+// "light rain" -> "Light rain"
+pub fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    // Numbers keep the text they are printed with, so that structured output
+    // is rounded the same way as the plain text output
+    Number { value: String, unit: String },
+    Text(String),
+    Range { high: Box<Value>, low: Box<Value> },
+    Null,
+}
+
+impl Value {
+    pub fn number(value: impl fmt::Display, unit: &str) -> Self {
+        Self::Number {
+            value: value.to_string(),
+            unit: unit.to_string(),
+        }
+    }
+
+    pub fn text(value: impl fmt::Display) -> Self {
+        Self::Text(value.to_string())
+    }
+
+    pub fn range(high: Value, low: Value) -> Self {
+        Self::Range {
+            high: Box::new(high),
+            low: Box::new(low),
+        }
+    }
+
+    // Values as returned by the API, in the display units
+    pub fn temp(temp: f32, units: &Units) -> Self {
+        let (temp, unit) = units.temp(temp);
+        Self::number(temp, unit)
+    }
+
+    pub fn celsius(temp: f32, units: &Units) -> Self {
+        let (temp, unit) = units.temp_from_celsius(temp);
+        Self::number(temp, unit)
+    }
+
+    pub fn speed(speed: f32, units: &Units) -> Self {
+        let (speed, unit) = units.speed(speed);
+        Self::number(speed, unit)
+    }
+
+    pub fn pressure(pressure: f32, units: &Units) -> Self {
+        let (pressure, unit) = units.pressure(pressure);
+        Self::number(pressure, unit)
+    }
+
+    pub fn distance(distance: f32, units: &Units) -> Self {
+        let (distance, unit) = units.distance(distance);
+        Self::number(distance, unit)
+    }
+
+    pub fn precipitation(precipitation: f32, units: &Units) -> Self {
+        let (precipitation, unit) = units.precipitation(precipitation);
+        Self::number(precipitation, unit)
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == Self::Null
+    }
+
+    fn cells(&self) -> Vec<String> {
+        match self {
+            Self::Number { value, .. } => vec![value.to_owned()],
+            Self::Text(text) => vec![text.to_owned()],
+            Self::Range { high, low } => [high.cells(), low.cells()].concat(),
+            Self::Null => vec![String::new()],
+        }
+    }
+}
+
+impl From<Option<Value>> for Value {
+    fn from(value: Option<Value>) -> Self {
+        value.unwrap_or(Self::Null)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number { value, unit } => write!(f, "{}{}", value, unit),
+            Self::Text(text) => write!(f, "{}", text),
+            Self::Range { high, low } => write!(f, "{}, {}", high, low),
+            Self::Null => Ok(()),
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Number { value, .. } => match (value.parse::<i64>(), value.parse::<f64>()) {
+                (Ok(value), _) => serializer.serialize_i64(value),
+                (_, Ok(value)) => serializer.serialize_f64(value),
+                _ => serializer.serialize_str(value),
+            },
+            Self::Text(text) => serializer.serialize_str(text),
+            Self::Range { high, low } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("high", high)?;
+                map.serialize_entry("low", low)?;
+                map.end()
+            }
+            Self::Null => serializer.serialize_none(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Field {
+    pub name: String,
+    pub label: String,
+    pub value: Value,
+}
+
+impl Field {
+    pub fn new(name: &str, label: &str, value: Value) -> Self {
+        Self {
+            name: name.to_string(),
+            label: label.to_string(),
+            value,
+        }
+    }
+
+    // Plain text rendering used by `--print` without `--format`
+    pub fn text(&self, verbose: bool) -> String {
+        match (&self.value, verbose) {
+            (Value::Null, _) => format!("No data to print for option {}", self.name),
+            (Value::Range { high, low }, true) => format!("High: {}, Low: {}", high, low),
+            (value, true) => format!("{}: {}", self.label, value),
+            (value, false) => value.to_string(),
+        }
+    }
+
+    fn headers(&self) -> Vec<String> {
+        match &self.value {
+            Value::Range { .. } => {
+                vec![format!("{}_high", self.name), format!("{}_low", self.name)]
+            }
+            _ => vec![self.name.to_owned()],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Record {
+    pub fields: Vec<Field>,
+}
+
+impl Record {
+    pub fn push(&mut self, field: Field) {
+        self.fields.push(field);
+    }
+}
+
+// Serialized as a map so that fields keep the order they were requested in
+impl Serialize for Record {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+        for field in self.fields.iter() {
+            map.serialize_entry(&field.name, &field.value)?;
+        }
+        map.end()
+    }
+}
+
+fn delimited(records: &[Record], delimiter: u8) -> String {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(vec![]);
+
+    if let Some(record) = records.first() {
+        let headers = record.fields.iter().flat_map(|field| field.headers());
+        writer
+            .write_record(headers)
+            .expect("Could not write header row!");
+    }
+
+    for record in records.iter() {
+        let cells = record.fields.iter().flat_map(|field| field.value.cells());
+        writer.write_record(cells).expect("Could not write row!");
+    }
+
+    let bytes = writer.into_inner().expect("Could not flush rows!");
+    String::from_utf8(bytes).expect("Rows are not valid UTF-8!")
+}
+
+// Renders records in a structured format. A single record is rendered as an
+// object rather than a list of one when `single` is set
+pub fn render(records: &[Record], format: Format, single: bool) -> String {
+    match (format, single, records.first()) {
+        (Format::Json, true, Some(record)) => {
+            serde_json::to_string_pretty(record).expect("Could not serialize record!") + "\n"
+        }
+        (Format::Json, _, _) => {
+            serde_json::to_string_pretty(records).expect("Could not serialize records!") + "\n"
+        }
+        (Format::Yaml, true, Some(record)) => {
+            serde_yaml::to_string(record).expect("Could not serialize record!")
+        }
+        (Format::Yaml, _, _) => {
+            serde_yaml::to_string(records).expect("Could not serialize records!")
+        }
+        (Format::Csv, _, _) => delimited(records, b','),
+        (Format::Tsv, _, _) => delimited(records, b'\t'),
+        (Format::Text, _, _) => records
+            .iter()
+            .flat_map(|record| record.fields.iter().map(|field| field.text(false) + "\n"))
+            .collect(),
+    }
+}
@@ -0,0 +1,264 @@
+use crate::client::error::Error;
+
+use super::record::{Record, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+// Subset of Rust's format spec: [[fill]align][0][width][.precision]
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Spec {
+    fill: Option<char>,
+    align: Option<Align>,
+    zero: bool,
+    width: Option<usize>,
+    precision: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Placeholder { name: String, spec: Spec },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+fn align(c: char) -> Option<Align> {
+    match c {
+        '<' => Some(Align::Left),
+        '>' => Some(Align::Right),
+        '^' => Some(Align::Center),
+        _ => None,
+    }
+}
+
+fn digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<usize> {
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+        number.push(c);
+    }
+    number.parse().ok()
+}
+
+impl Spec {
+    fn parse(spec: &str) -> Result<Self, Error> {
+        let invalid = || Error::Template(format!("invalid format spec `{}`", spec));
+        let mut parsed = Spec::default();
+
+        let chars: Vec<char> = spec.chars().collect();
+        let rest = match (chars.first(), chars.get(1).copied().and_then(align)) {
+            (Some(fill), Some(align)) => {
+                parsed.fill = Some(*fill);
+                parsed.align = Some(align);
+                &chars[2..]
+            }
+            (Some(c), None) if align(*c).is_some() => {
+                parsed.align = align(*c);
+                &chars[1..]
+            }
+            _ => &chars[..],
+        };
+
+        let rest: String = rest.iter().collect();
+        let mut chars = rest.chars().peekable();
+
+        if chars.next_if_eq(&'0').is_some() {
+            parsed.zero = true;
+        }
+        parsed.width = digits(&mut chars);
+
+        if chars.next_if_eq(&'.').is_some() {
+            parsed.precision = Some(digits(&mut chars).ok_or_else(invalid)?);
+        }
+
+        match chars.next() {
+            Some(_) => Err(invalid()),
+            None => Ok(parsed),
+        }
+    }
+
+    fn apply(&self, value: &Value) -> String {
+        let number = matches!(value, Value::Number { .. });
+        let text = match (value, self.precision) {
+            (Value::Number { value, .. }, Some(precision)) => match value.parse::<f64>() {
+                Ok(number) => format!("{:.*}", precision, number),
+                Err(_) => value.to_owned(),
+            },
+            (Value::Number { value, .. }, None) => value.to_owned(),
+            (Value::Text(text), Some(precision)) => text.chars().take(precision).collect(),
+            (Value::Text(text), None) => text.to_owned(),
+            (Value::Range { high, low }, _) => {
+                format!("{}, {}", self.apply(high), self.apply(low))
+            }
+            (Value::Null, _) => String::new(),
+        };
+
+        let width = self.width.unwrap_or(0);
+        let len = text.chars().count();
+        if len >= width {
+            return text;
+        }
+
+        // Zero padding goes after the sign, like Rust's {:05}
+        if self.zero && number && self.align.is_none() {
+            let (sign, digits) = match text.strip_prefix('-') {
+                Some(digits) => ("-", digits),
+                None => ("", text.as_str()),
+            };
+            return format!("{}{}{}", sign, "0".repeat(width - len), digits);
+        }
+
+        let fill = self.fill.unwrap_or(' ');
+        let pad = |n: usize| fill.to_string().repeat(n);
+        let default = match number {
+            true => Align::Right,
+            false => Align::Left,
+        };
+
+        match self.align.unwrap_or(default) {
+            Align::Left => format!("{}{}", text, pad(width - len)),
+            Align::Right => format!("{}{}", pad(width - len), text),
+            Align::Center => {
+                let left = (width - len) / 2;
+                format!("{}{}{}", pad(left), text, pad(width - len - left))
+            }
+        }
+    }
+}
+
+impl Template {
+    // Parses "{name}: {temp:.0}{unit_temp}" style templates. Braces are
+    // escaped by doubling them, as in Rust format strings
+    pub fn parse(template: &str) -> Result<Self, Error> {
+        let mut segments = vec![];
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.next_if_eq(&'{').is_some() => literal.push('{'),
+                '}' if chars.next_if_eq(&'}').is_some() => literal.push('}'),
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => {
+                                return Err(Error::Template(format!(
+                                    "unclosed placeholder `{{{}`",
+                                    placeholder
+                                )))
+                            }
+                        }
+                    }
+
+                    let (name, spec) = match placeholder.split_once(':') {
+                        Some((name, spec)) => (name.trim(), Spec::parse(spec)?),
+                        None => (placeholder.trim(), Spec::default()),
+                    };
+
+                    if name.is_empty() {
+                        return Err(Error::Template("empty placeholder `{}`".to_string()));
+                    }
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Placeholder {
+                        name: name.to_string(),
+                        spec,
+                    });
+                }
+                '}' => {
+                    return Err(Error::Template(
+                        "unmatched `}`, use `}}` for a literal brace".to_string(),
+                    ))
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    // Looks up `name` in the record. Besides the record's own fields, this
+    // resolves `unit_<field>` to a field's unit and `<field>_high`/`<field>_low`
+    // to either end of a range
+    fn lookup(record: &Record, name: &str) -> Option<Value> {
+        let field = |name: &str| {
+            record
+                .fields
+                .iter()
+                .find(|field| field.name == name)
+                .map(|field| field.value.clone())
+        };
+
+        if let Some(value) = field(name) {
+            return Some(value);
+        }
+
+        if let Some(name) = name.strip_prefix("unit_") {
+            return match field(name)? {
+                Value::Number { unit, .. } => Some(Value::Text(unit)),
+                Value::Range { high, .. } => match *high {
+                    Value::Number { unit, .. } => Some(Value::Text(unit)),
+                    _ => Some(Value::Null),
+                },
+                _ => Some(Value::Null),
+            };
+        }
+
+        for (suffix, high) in [("_high", true), ("_low", false)] {
+            if let Some(Value::Range { high: h, low: l }) =
+                name.strip_suffix(suffix).and_then(field)
+            {
+                return Some(match high {
+                    true => *h,
+                    false => *l,
+                });
+            }
+        }
+
+        None
+    }
+
+    pub fn render(&self, record: &Record) -> Result<String, Error> {
+        let mut rendered = String::new();
+
+        for segment in self.segments.iter() {
+            match segment {
+                Segment::Literal(literal) => rendered.push_str(literal),
+                Segment::Placeholder { name, spec } => match Self::lookup(record, name) {
+                    Some(value) => rendered.push_str(&spec.apply(&value)),
+                    None => {
+                        let known = record
+                            .fields
+                            .iter()
+                            .map(|field| field.name.as_str())
+                            .collect::<Vec<&str>>()
+                            .join(", ");
+
+                        return Err(Error::Template(format!(
+                            "unknown placeholder `{{{}}}`, expected one of: {}",
+                            name, known
+                        )));
+                    }
+                },
+            }
+        }
+
+        Ok(rendered)
+    }
+}
@@ -1,30 +1,342 @@
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
 use clap::Parser;
+use itertools::Itertools;
 use openweathercli::{
-    data::{current_weather::CurrentWeather, data::Data},
-    options::{args::Args, config::Config},
+    client::{client::OpenWeatherClient, error::Error},
+    data::{
+        air_pollution::AirPollution,
+        astro::Astro,
+        bar::Bar,
+        batch::{entries, Batch},
+        convert::check_time_format,
+        current_weather::CurrentWeather,
+        five_day_forecast::FiveDayForecast,
+        geocoding::Reverse,
+        record::render,
+        template::Template,
+    },
+    options::{
+        args::{Args, Command, ConfigCommand, LocationCommand},
+        config::{
+            parse_value, remove_location, save_location, set, validate_files, Config, Location,
+            UnitsConfig,
+        },
+        format::Format,
+        init::{ask_secret, init},
+        key::{missing, store},
+        options::{
+            check_env, get_base_url, get_datetime_format, get_key, get_lat, get_lon, get_template,
+            get_time_format, has_location, load_dotenv, settings,
+        },
+    },
 };
 
-#[tokio::main]
-async fn main() -> Result<(), reqwest::Error> {
-    let args = Args::parse();
-    let config = Config::load();
+// `owcli location add|list|remove`
+fn location(action: &LocationCommand, config: &Config) -> Result<(), Error> {
+    match action {
+        LocationCommand::Add {
+            name,
+            lat,
+            lon,
+            city,
+            state,
+            country,
+            zip,
+            units,
+            lang,
+            default,
+        } => {
+            let location = Location {
+                lat: *lat,
+                lon: *lon,
+                city: city.clone(),
+                state: state.clone(),
+                country: country.clone(),
+                zip: zip.clone(),
+                units: units.map(UnitsConfig::System),
+                lang: lang.clone(),
+                ..Location::default()
+            };
+            let path = save_location(config, name, &location, *default)?;
+            println!(
+                "Saved {} as {} in {}",
+                location.describe(),
+                name,
+                path.display()
+            );
+        }
+        LocationCommand::List => {
+            let locations = config.locations.clone().unwrap_or_default();
+            if locations.is_empty() {
+                println!("No locations saved in {}", config.edit_path().display());
+            }
+
+            for (name, location) in locations.iter().sorted_by_key(|(name, _)| *name) {
+                match config.default_location.as_ref() == Some(name) {
+                    true => println!("{}: {} (default)", name, location.describe()),
+                    false => println!("{}: {}", name, location.describe()),
+                }
+            }
+        }
+        LocationCommand::Remove { name } => {
+            let path = remove_location(name, config)?;
+            println!("Removed {} from {}", name, path.display());
+        }
+    }
+
+    Ok(())
+}
+
+// The current weather of several locations, compared side by side. A location
+// that fails is reported along with the others rather than stopping the batch
+async fn run_batch(
+    client: &OpenWeatherClient,
+    args: &Args,
+    config: &Config,
+    api: &str,
+    template: Option<&Template>,
+) -> Result<(), Error> {
+    if api != "current" {
+        return Err(Error::BatchUnsupported(format!("--api {}", api)));
+    }
+    if args.output.is_some() {
+        return Err(Error::BatchUnsupported("--output".to_string()));
+    }
+
+    let entries = entries(args, config)?;
+    let total = entries.len();
+    let batch = Batch::get(client, args, entries, args.concurrency.into()).await;
+
+    if let Some(summary) = args.summary {
+        print!("{}", batch.summary(summary, args));
+    }
+
+    if let Some(template) = template {
+        print!("{}", batch.render(template, args)?);
+    }
+
+    let opts: Vec<&str> = match &args.print {
+        Some(opts) => opts.split(',').collect(),
+        None => vec![],
+    };
+
+    // The table is printed for --print, or when no other output was asked for
+    let table = args.print.is_some() || (args.summary.is_none() && template.is_none());
+    match args.format {
+        Format::Text if table => print!("{}", batch.table(&opts, args)),
+        Format::Text => {}
+        format => print!("{}", render(&batch.records(&opts, args), format, false)),
+    }
+
+    match batch.failed() {
+        0 => Ok(()),
+        failed => Err(Error::BatchFailed { failed, total }),
+    }
+}
+
+// `owcli config init|show|set`
+async fn configure(action: &ConfigCommand, args: &Args, config: &Config) -> Result<(), Error> {
+    match action {
+        ConfigCommand::Init => {
+            let path = init(args, config).await?;
+            println!("Saved the configuration to {}", path.display());
+        }
+        ConfigCommand::Show { origin } => {
+            for setting in settings(args, config)? {
+                match origin {
+                    true => println!("{}: {}  # {}", setting.name, setting.value, setting.origin),
+                    false => println!("{}: {}", setting.name, setting.value),
+                }
+            }
+            print!("{}", config.sections(*origin));
+        }
+        ConfigCommand::Validate => unreachable!("Validated before loading the configuration"),
+        ConfigCommand::Set { key, value } => {
+            let path = set(config, &[(key, parse_value(value))])?;
+            println!("Set {} in {}", key, path.display());
+        }
+        ConfigCommand::SetKey => match ask_secret("OpenWeather API key: ")?.as_str() {
+            "" => return Err(Error::Aborted("no key given".to_string())),
+            key => {
+                store(key)?;
+                println!("Saved the API key to the system keyring");
+            }
+        },
+    }
+
+    Ok(())
+}
+
+// `owcli config validate`, which reports every file rather than stopping at
+// the first invalid one
+fn validate(explicit: Option<&Path>) -> Result<(), Error> {
+    let results = validate_files(explicit);
+    if results.is_empty() {
+        println!("No configuration files found");
+    }
+
+    for (path, result) in results.iter() {
+        match result {
+            Ok(()) => println!("{}: ok", path.display()),
+            Err(e) => println!("{}: {}", path.display(), e),
+        }
+    }
+    check_env()?;
+
+    match results.iter().filter(|(_, result)| result.is_err()).count() {
+        0 => Ok(()),
+        invalid => Err(Error::InvalidConfig(format!(
+            "{} of {} files have errors",
+            invalid,
+            results.len()
+        ))),
+    }
+}
+
+async fn run(args: &Args) -> Result<(), Error> {
+    let explicit = match &args.config {
+        Some(path) => Some(path.to_owned()),
+        None => std::env::var_os("OWCLI_CONFIG").map(PathBuf::from),
+    };
+    if let Some(Command::Config {
+        action: ConfigCommand::Validate,
+    }) = &args.command
+    {
+        return validate(explicit.as_deref());
+    }
+
+    let config = &Config::load(explicit.as_deref())?;
+    check_env()?;
+
+    match &args.command {
+        Some(Command::Location { action }) => return location(action, config),
+        Some(Command::Config {
+            action:
+                action @ (ConfigCommand::Init | ConfigCommand::Set { .. } | ConfigCommand::SetKey),
+        }) => return configure(action, args, config).await,
+        _ => {}
+    }
+
+    // Each location of a batch applies its own settings over the configuration
+    let batch = args.location.len() > 1 || args.locations_file.is_some();
+    let config = &match batch {
+        true => config.clone(),
+        false => config.clone().for_location(
+            args.location.first().map(String::as_str),
+            has_location(args),
+        )?,
+    };
+
+    if let Some(Command::Config { action }) = &args.command {
+        return configure(action, args, config).await;
+    }
 
     let api = match &args.api {
         Some(api) => api.to_owned(),
         None => "current".to_string(),
     };
 
-    // Set up this way to make it easier to add other options later
+    // Astronomical data is computed locally, so a key is only needed to
+    // geocode a place name
+    let coords = get_lat(args, config).is_some() && get_lon(args, config).is_some();
+    let key = match get_key(args, config)? {
+        Some(key) => key,
+        None if api == "astro" && coords => String::new(),
+        None => return Err(missing()),
+    };
+    let client = match get_base_url(args, config) {
+        Some(base_url) => OpenWeatherClient::new(&key).with_base_url(&base_url),
+        None => OpenWeatherClient::new(&key),
+    };
+
+    // Parsed up front so that a template that does not parse fails before any
+    // request is made. Unknown placeholders are only found when rendering, as
+    // the fields depend on the response
+    let template = match get_template(args, config) {
+        Some(template) => Some(Template::parse(&template)?),
+        None => None,
+    };
+
+    let formats = [
+        get_time_format(args, config),
+        get_datetime_format(args, config),
+    ];
+    for format in formats.into_iter().flatten() {
+        check_time_format(&format)?;
+    }
+
+    let bar = match args.output {
+        Some(_) => Some(Bar::new(config.bar.clone())?),
+        None => None,
+    };
+
+    if batch {
+        return run_batch(&client, args, config, &api, template.as_ref()).await;
+    }
+
     let data = match api.as_str() {
-        "current" => CurrentWeather::get(&args, &config).await?,
-        _ => CurrentWeather::get(&args, &config).await?,
+        "current" => CurrentWeather::get(&client, args, config).await?,
+        "forecast" => FiveDayForecast::get(&client, args, config).await?,
+        "astro" => Astro::get(&client, args, config).await?,
+        "air" | "air-forecast" | "air-history" => {
+            AirPollution::get(&client, args, config, &api).await?
+        }
+        "reverse" => Reverse::get(&client, args, config).await?,
+        _ => return Err(Error::UnknownApi(api)),
     };
+    let dataset = data.dataset();
+
+    if let Some(summary) = args.summary {
+        print!("{}", dataset.summary(summary, args, config));
+    }
 
-    if let Some(opts) = &args.print {
-        opts.split(',').for_each(|opt| match &data {
-            Data::CurrentWeather(dtype) => dtype.print(opt, &args, &config),
-        });
+    if let (Some(output), Some(bar)) = (args.output, &bar) {
+        // Status bars show a single block, so forecasts use their first slot
+        if let Some(record) = dataset.template_records(args, config).first() {
+            print!("{}", bar.render(output, record)?);
+        }
+    }
+
+    if let Some(template) = &template {
+        for record in dataset.template_records(args, config).iter() {
+            println!("{}", template.render(record)?);
+        }
+    }
+
+    let opts: Vec<&str> = match &args.print {
+        Some(opts) => opts.split(',').collect(),
+        None => vec![],
+    };
+
+    match args.format {
+        Format::Text => opts.iter().for_each(|opt| dataset.print(opt, args, config)),
+        format => print!(
+            "{}",
+            render(
+                &dataset.records(&opts, args, config),
+                format,
+                dataset.single()
+            )
+        ),
     }
 
     Ok(())
 }
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    load_dotenv();
+    let args = Args::parse();
+
+    match run(&args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
@@ -1,9 +1,26 @@
-use clap::Parser;
+use std::path::PathBuf;
 
-#[derive(Parser, Debug)]
+use clap::{Parser, Subcommand};
+
+use super::{
+    format::{Format, Icons, Output, Summary, Tz, WindDir},
+    units::{DistanceUnit, PrecipUnit, PressureUnit, TempUnit, UnitSystem, WindUnit},
+};
+
+#[derive(Parser, Clone, Debug)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[arg(long)]
+    pub config: Option<PathBuf>, // Configuration file laid over the discovered ones
     #[arg(long)]
     pub api: Option<String>,
+    #[arg(long, value_delimiter = ',', conflicts_with_all = ["lat", "lon", "city", "zip"])]
+    pub location: Vec<String>, // Names of locations saved in the config file
+    #[arg(long, conflicts_with_all = ["lat", "lon", "city", "zip"])]
+    pub locations_file: Option<PathBuf>, // CSV of places to compare
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+    pub concurrency: u16, // Locations of a batch fetched at once
     #[arg(long, allow_hyphen_values(true))]
     pub lat: Option<f64>,
     #[arg(long, allow_hyphen_values(true))]
@@ -17,13 +34,120 @@ pub struct Args {
     #[arg(long)]
     pub zip: Option<String>,
     #[arg(long)]
-    pub units: Option<String>,
+    pub pick: Option<usize>, // Which of several places matching --city to use, from 1
+    #[arg(long)]
+    pub save_location: Option<String>, // Save the geocoded place under this name
+    #[arg(long, value_enum, ignore_case = true)]
+    pub units: Option<UnitSystem>,
+    #[arg(long, value_enum, ignore_case = true)]
+    pub temp_unit: Option<TempUnit>, // Overrides the unit system for temperatures
+    #[arg(long, value_enum, ignore_case = true)]
+    pub wind_unit: Option<WindUnit>, // ...for wind speeds and gusts
+    #[arg(long, value_enum, ignore_case = true)]
+    pub pressure_unit: Option<PressureUnit>, // ...for pressure
+    #[arg(long, value_enum, ignore_case = true)]
+    pub distance_unit: Option<DistanceUnit>, // ...for visibility
+    #[arg(long, value_enum, ignore_case = true)]
+    pub precip_unit: Option<PrecipUnit>, // ...for rain and snow
+    #[arg(long, value_enum)]
+    pub wind_dir_format: Option<WindDir>, // Degrees, compass points or arrows
+    #[arg(long)]
+    pub lang: Option<String>, // Language code for compass points, e.g. de
+    #[arg(long)]
+    pub base_url: Option<String>, // e.g. http://localhost:8080 for a mock server
     #[arg(short, long)]
     pub key: Option<String>,
     #[arg(short, long)]
     pub print: Option<String>, // Print user specified information
+    #[arg(long, value_enum, default_value_t)]
+    pub format: Format, // Render --print options as structured data
+    #[arg(long, value_enum)]
+    pub output: Option<Output>, // Print a status bar block
     #[arg(short, long)]
-    pub summary: Option<String>, // Print general summary of data
+    pub template: Option<String>, // Template string, or the name of one in the config file
+    #[arg(short, long, value_enum, num_args = 0..=1, default_missing_value = "full")]
+    pub summary: Option<Summary>, // Print general summary of data
+    #[arg(long, value_enum)]
+    pub icons: Option<Icons>, // Glyph set for the glyph print option
+    #[arg(long, value_enum)]
+    pub tz: Option<Tz>, // Zone to show times in
+    #[arg(long)]
+    pub time_format: Option<String>, // strftime format for times of day, e.g. sunrise
+    #[arg(long)]
+    pub datetime_format: Option<String>, // strftime format for dates and times
+    #[arg(long)]
+    pub date: Option<String>, // Date, or date and time, for --api astro. Defaults to now
+    #[arg(long)]
+    pub start: Option<String>, // Start of --api air-history. Defaults to a day before --end
+    #[arg(long)]
+    pub end: Option<String>, // End of --api air-history. Defaults to now
     #[arg(short, long, action)]
     pub verbose: bool,
+    #[arg(long, action)]
+    pub daily: bool, // Aggregate forecast data per day instead of per 3 hour slot
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+    /// Manage the locations saved in the config file
+    Location {
+        #[command(subcommand)]
+        action: LocationCommand,
+    },
+    /// Set up, inspect and edit the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum ConfigCommand {
+    /// Set up a configuration file interactively
+    Init,
+    /// Print the settings in effect
+    Show {
+        /// Also print where each setting came from
+        #[arg(long, action)]
+        origin: bool,
+    },
+    /// Check the configuration files
+    Validate,
+    /// Set a key, e.g. `units.wind knots`, in the configuration file
+    Set { key: String, value: String },
+    /// Store the API key in the system keyring, asking for it without echo
+    SetKey,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum LocationCommand {
+    /// Save a location under a name
+    Add {
+        name: String,
+        #[arg(long, allow_hyphen_values(true))]
+        lat: Option<f64>,
+        #[arg(long, allow_hyphen_values(true))]
+        lon: Option<f64>,
+        #[arg(long)]
+        city: Option<String>,
+        #[arg(long)]
+        state: Option<String>,
+        #[arg(long)]
+        country: Option<String>,
+        #[arg(long)]
+        zip: Option<String>,
+        /// Units used for this location only
+        #[arg(long, value_enum, ignore_case = true)]
+        units: Option<UnitSystem>,
+        /// Language used for this location only
+        #[arg(long)]
+        lang: Option<String>,
+        /// Also make it the default_location
+        #[arg(long, action)]
+        default: bool,
+    },
+    /// List the saved locations
+    List,
+    /// Remove a saved location
+    Remove { name: String },
 }
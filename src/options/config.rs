@@ -1,49 +1,653 @@
-use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
-#[derive(Debug, Deserialize)]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::error::Error,
+    data::{convert::check_time_format, template::Template},
+};
+
+use super::{
+    format::{Icons, Tz, WindDir},
+    units::{DistanceUnit, PrecipUnit, PressureUnit, TempUnit, UnitSystem, WindUnit},
+};
+
+// Temperature band for status bar output. Bands are checked in order and the
+// first whose `below` exceeds the temperature (in the display units) applies
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Band {
+    pub below: Option<f32>,
+    pub class: String,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BarConfig {
+    pub text: Option<String>,
+    pub short_text: Option<String>,
+    pub tooltip: Option<String>,
+    pub percentage: Option<String>,
+    pub bands: Option<Vec<Band>>,
+    pub colors: Option<HashMap<String, String>>,
+}
+
+// Per-quantity overrides on top of the unit system, e.g.
+// units: { system: metric, wind: knots, pressure: inhg }
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct UnitsSection {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<UnitSystem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temp: Option<TempUnit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wind: Option<WindUnit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<PressureUnit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance: Option<DistanceUnit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precip: Option<PrecipUnit>,
+}
+
+// `units` is either just the unit system, or a section with overrides
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(
+    untagged,
+    expecting = "`units` to be metric, imperial, standard or a section of valid units"
+)]
+pub enum UnitsConfig {
+    System(UnitSystem),
+    Section(UnitsSection),
+}
+
+impl UnitsConfig {
+    pub fn section(&self) -> UnitsSection {
+        match self {
+            Self::System(system) => UnitsSection {
+                system: Some(*system),
+                ..Default::default()
+            },
+            Self::Section(section) => section.clone(),
+        }
+    }
+}
+
+// A place saved under a name in `locations`, given either by coordinates or
+// by a geocoding query, with optional units and language of its own
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Location {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lat: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lon: Option<f64>,
+    // Display name of a geocoded place
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units: Option<UnitsConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+}
+
+// Latitudes lie within ±90 and longitudes within ±180 degrees
+fn check_coordinate(name: &str, value: f64) -> Result<(), String> {
+    let limit = match name {
+        "lat" => 90.0,
+        _ => 180.0,
+    };
+
+    match (-limit..=limit).contains(&value) {
+        true => Ok(()),
+        false => Err(format!(
+            "`{}` must be between -{} and {}, not {}",
+            name, limit, limit, value
+        )),
+    }
+}
+
+impl Location {
+    pub fn validate(&self, name: &str) -> Result<(), Error> {
+        for (coordinate, value) in [("lat", self.lat), ("lon", self.lon)] {
+            if let Some(value) = value {
+                check_coordinate(coordinate, value)
+                    .map_err(|e| Error::InvalidLocation(format!("{}: {}", name, e)))?;
+            }
+        }
+
+        match self {
+            Self {
+                lat: Some(_),
+                lon: Some(_),
+                ..
+            }
+            | Self { city: Some(_), .. }
+            | Self {
+                zip: Some(_),
+                country: Some(_),
+                ..
+            } => Ok(()),
+            _ => Err(Error::InvalidLocation(format!(
+                "{} needs lat and lon, a city, or a zip and country",
+                name
+            ))),
+        }
+    }
+
+    // e.g. "Springfield, Illinois, US (39.7990, -89.6440)" or "94040, US"
+    pub fn describe(&self) -> String {
+        let place: Vec<String> = [
+            self.name.clone().or(self.city.clone()),
+            self.zip.clone(),
+            self.state.clone(),
+            self.country.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        match (place.is_empty(), self.lat, self.lon) {
+            (true, Some(lat), Some(lon)) => format!("{:.4}, {:.4}", lat, lon),
+            (false, Some(lat), Some(lon)) => {
+                format!("{} ({:.4}, {:.4})", place.join(", "), lat, lon)
+            }
+            _ => place.join(", "),
+        }
+    }
+}
+
+// Unknown keys are rejected, so that misspelled settings are not silently
+// ignored
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     pub key: Option<String>,
-    pub units: Option<String>,
+    // Command printing the key, e.g. `pass show owm`
+    pub key_command: Option<String>,
+    // File holding the key, only readable by its owner
+    pub key_file: Option<String>,
+    pub units: Option<UnitsConfig>,
     pub lat: Option<String>,
     pub lon: Option<String>,
     pub city: Option<String>,
     pub state: Option<String>,
     pub country: Option<String>,
     pub zip: Option<String>,
+    pub base_url: Option<String>,
+    pub templates: Option<HashMap<String, String>>,
+    pub bar: Option<BarConfig>,
+    pub icons: Option<Icons>,
+    pub tz: Option<Tz>,
+    pub time_format: Option<String>,
+    pub datetime_format: Option<String>,
+    pub wind_dir_format: Option<WindDir>,
+    pub lang: Option<String>,
+    pub locations: Option<HashMap<String, Location>>,
+    pub default_location: Option<String>,
+    // File that edits such as `owcli location add` are written to
+    #[serde(skip)]
+    pub file: Option<PathBuf>,
+    // Where each top level key was last set, for `owcli config show --origin`
+    #[serde(skip)]
+    pub origins: HashMap<String, String>,
+    // The merged files, for `owcli config show`
+    #[serde(skip)]
+    pub yaml: serde_yaml::Mapping,
+}
+
+// Configuration files found on every run, from lowest to highest precedence:
+// system wide, per user, and the nearest `.owcli.yaml` in the current
+// directory or one of its parents
+fn discover() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from("/etc/owcli/config.yaml"), Config::path()];
+
+    let cwd = std::env::current_dir().unwrap_or_default();
+    if let Some(project) = cwd
+        .ancestors()
+        .map(|dir| dir.join(".owcli.yaml"))
+        .find(|path| path.is_file())
+    {
+        paths.push(project);
+    }
+
+    paths
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            key: None,
-            units: None,
-            lat: None,
-            lon: None,
-            city: None,
-            state: None,
-            country: None,
-            zip: None,
+// Reads one configuration file as YAML, checking that it is a valid
+// configuration on its own. Empty files are empty mappings
+fn read_layer(path: &Path) -> Result<serde_yaml::Mapping, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_layer(&text)
+}
+
+fn parse_layer(text: &str) -> Result<serde_yaml::Mapping, String> {
+    if text.trim().is_empty() {
+        return Ok(serde_yaml::Mapping::new());
+    }
+
+    let config: Config = serde_yaml::from_str(text).map_err(|e| e.to_string())?;
+    config.check()?;
+    serde_yaml::from_str(text).map_err(|e| e.to_string())
+}
+
+// The configuration files that exist, in order of precedence, for `owcli
+// config validate`. Each is checked on its own, including its templates and
+// time formats, which are otherwise only checked when used. A
+// `default_location` may name a location from another file
+pub fn validate_files(explicit: Option<&Path>) -> Vec<(PathBuf, Result<(), String>)> {
+    let mut paths: Vec<PathBuf> = discover().into_iter().filter(|p| p.exists()).collect();
+    paths.extend(explicit.map(Path::to_path_buf));
+
+    let mut merged = serde_yaml::Mapping::new();
+    let mut default_location = None;
+    let mut results = vec![];
+
+    for (n, path) in paths.into_iter().enumerate() {
+        let result = read_layer(&path).and_then(|yaml| {
+            let text = serde_yaml::to_string(&yaml).map_err(|e| e.to_string())?;
+            let config: Config = serde_yaml::from_str(&text).map_err(|e| e.to_string())?;
+            config.lint()?;
+
+            if yaml.contains_key("default_location") {
+                default_location = Some(n);
+            }
+            merge(&mut merged, yaml);
+            Ok(())
+        });
+        results.push((path, result));
+    }
+
+    if let Some(n) = default_location {
+        let name = merged
+            .get("default_location")
+            .and_then(|name| name.as_str());
+        let locations = merged.get("locations").and_then(|l| l.as_mapping());
+        if let (Some(name), Some((_, result))) = (name, results.get_mut(n)) {
+            if !locations.is_some_and(|locations| locations.contains_key(name)) {
+                *result = Err(format!("default_location {} is not in `locations`", name));
+            }
+        }
+    }
+
+    results
+}
+
+// `units: imperial` is short for `units: {system: imperial}`
+fn units_section(units: &serde_yaml::Value) -> serde_yaml::Value {
+    match units {
+        serde_yaml::Value::Mapping(_) => units.clone(),
+        system => serde_yaml::Mapping::from_iter([("system".into(), system.clone())]).into(),
+    }
+}
+
+// Lays `layer` over `base`. Its top level keys replace those of `base`, except
+// for sections such as `units`, `templates` and `locations`, which are merged
+// entry by entry
+fn merge(base: &mut serde_yaml::Mapping, layer: serde_yaml::Mapping) {
+    for (key, mut value) in layer {
+        // A unit system alone merges with a units section as if it were one
+        if let Some(units) = base.get_mut("units").filter(|_| key == "units") {
+            if units.is_mapping() != value.is_mapping() {
+                *units = units_section(units);
+                value = units_section(&value);
+            }
+        }
+
+        if let (Some(serde_yaml::Value::Mapping(section)), serde_yaml::Value::Mapping(entries)) =
+            (base.get_mut(&key), &value)
+        {
+            section.extend(entries.clone());
+            continue;
         }
+        base.insert(key, value);
     }
 }
 
 impl Config {
-    pub fn load() -> Self {
+    pub fn path() -> PathBuf {
         let mut config_path = dirs::config_dir().unwrap_or_default();
         config_path.push("owcli/config.yaml");
+        config_path
+    }
 
-        match std::fs::File::open(&config_path) {
-            Ok(f) => match serde_yaml::from_reader(f) {
-                Ok(config) => config,
-                Err(e2) => {
-                    eprintln!("Unable to deserialize the configuration file: {}", e2);
-                    Self::default()
+    // Merges the discovered configuration files, followed by `explicit`, the
+    // file given with --config or OWCLI_CONFIG. Discovered files are optional
+    // and skipped with a warning when invalid, but an explicit file must exist
+    // and be valid
+    pub fn load(explicit: Option<&Path>) -> Result<Self, Error> {
+        let mut yaml = serde_yaml::Mapping::new();
+        let mut origins = HashMap::new();
+        let mut add = |path: &Path, layer: serde_yaml::Mapping| {
+            for key in layer.keys().filter_map(|key| key.as_str()) {
+                origins.insert(key.to_string(), path.display().to_string());
+            }
+            // Units are merged entry by entry, so each entry has its own origin
+            if let Some(serde_yaml::Value::Mapping(units)) = layer.get("units").map(units_section) {
+                for entry in units.keys().filter_map(|entry| entry.as_str()) {
+                    origins.insert(format!("units.{}", entry), path.display().to_string());
+                }
+            }
+            // So are saved locations, which are only removed from their own file
+            if let Some(serde_yaml::Value::Mapping(locations)) = layer.get("locations") {
+                for name in locations.keys().filter_map(|name| name.as_str()) {
+                    origins.insert(format!("locations.{}", name), path.display().to_string());
                 }
-            },
-            Err(e1) => {
-                eprintln!("Unable to open the configuration file: {}", e1);
-                Self::default()
+            }
+            merge(&mut yaml, layer);
+        };
+
+        for path in discover() {
+            if !path.exists() {
+                continue;
+            }
+            match read_layer(&path) {
+                Ok(layer) => add(&path, layer),
+                Err(e) => eprintln!(
+                    "Unable to read the configuration file {}: {}",
+                    path.display(),
+                    e
+                ),
             }
         }
+
+        if let Some(path) = explicit {
+            let layer = read_layer(path)
+                .map_err(|e| Error::ConfigFile(format!("{}: {}", path.display(), e)))?;
+            add(path, layer);
+        }
+
+        // Parsed from text like a single file, where scalars such as `lat: 52.52`
+        // may be read as strings
+        let text = serde_yaml::to_string(&yaml).map_err(|e| Error::ConfigFile(e.to_string()))?;
+        let mut config: Self =
+            serde_yaml::from_str(&text).map_err(|e| Error::ConfigFile(e.to_string()))?;
+        config.file = Some(explicit.map_or_else(Self::path, Path::to_path_buf));
+        config.origins = origins;
+        config.yaml = yaml;
+        Ok(config)
     }
+
+    // The sections of the merged files that are not single settings, as YAML,
+    // optionally noting the file each was last set in
+    pub fn sections(&self, origin: bool) -> String {
+        let mut text = String::new();
+
+        for key in ["default_location", "locations", "templates", "bar"] {
+            let Some(value) = self.yaml.get(key) else {
+                continue;
+            };
+            let mut section = serde_yaml::Mapping::new();
+            section.insert(key.into(), value.clone());
+            let yaml = serde_yaml::to_string(&section).unwrap_or_default();
+
+            match (origin, self.origins.get(key), yaml.split_once('\n')) {
+                (true, Some(path), Some((first, rest))) => {
+                    text += &format!("{}  # {}\n{}", first, path, rest)
+                }
+                _ => text += &yaml,
+            }
+        }
+
+        text
+    }
+
+    // Checks what the types alone do not: coordinates must be numbers in
+    // range, and saved locations must name a place
+    pub fn check(&self) -> Result<(), String> {
+        for (name, value) in [("lat", &self.lat), ("lon", &self.lon)] {
+            if let Some(value) = value {
+                let number = value
+                    .parse::<f64>()
+                    .map_err(|_| format!("`{}` must be a number, not {}", name, value))?;
+                check_coordinate(name, number)?;
+            }
+        }
+
+        for (name, location) in self.locations.iter().flatten() {
+            location.validate(name).map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    }
+
+    // Further checks of settings that are only used by some commands
+    fn lint(&self) -> Result<(), String> {
+        for format in [&self.time_format, &self.datetime_format]
+            .into_iter()
+            .flatten()
+        {
+            check_time_format(format).map_err(|e| e.to_string())?;
+        }
+
+        for (name, template) in self.templates.iter().flatten() {
+            Template::parse(template).map_err(|e| format!("template {}: {}", name, e))?;
+        }
+
+        Ok(())
+    }
+
+    // Where edits are written: the explicit configuration file if one was
+    // given, or else the user's
+    pub fn edit_path(&self) -> PathBuf {
+        self.file.clone().unwrap_or_else(Self::path)
+    }
+
+    // Applies the location chosen with --location, or else `default_location`
+    // unless `flags` gives a location on the command line, over the top level
+    // location fields
+    pub fn for_location(self, location: Option<&str>, flags: bool) -> Result<Self, Error> {
+        let name = match (location, &self.default_location) {
+            (Some(name), _) => name.to_string(),
+            (None, Some(name)) if !flags => name.to_string(),
+            _ => return Ok(self),
+        };
+
+        let location = self
+            .locations
+            .as_ref()
+            .and_then(|locations| locations.get(&name))
+            .cloned()
+            .ok_or_else(|| Error::UnknownLocation(name.clone()))?;
+        location.validate(&name)?;
+
+        Ok(self.with_location(&name, location))
+    }
+
+    // Replaces the top level location fields with those of the location called
+    // `name`. Its units and lang, when set, override the configured ones
+    pub fn with_location(mut self, name: &str, location: Location) -> Self {
+        let origin = format!("location {}", name);
+        for key in ["lat", "lon", "city", "state", "country", "zip"] {
+            self.origins.insert(key.to_string(), origin.clone());
+        }
+        if location.units.is_some() {
+            self.origins.retain(|key, _| !key.starts_with("units."));
+            self.origins.insert("units".to_string(), origin.clone());
+        }
+        if location.lang.is_some() {
+            self.origins.insert("lang".to_string(), origin);
+        }
+
+        self.lat = location.lat.map(|lat| lat.to_string());
+        self.lon = location.lon.map(|lon| lon.to_string());
+        self.city = location.city;
+        self.state = location.state;
+        self.country = location.country;
+        self.zip = location.zip;
+        self.units = location.units.or(self.units);
+        self.lang = location.lang.or(self.lang);
+        self
+    }
+}
+
+// Applies `edit` to the configuration file as YAML, creating the file if
+// needed. The new file replaces the old one only once fully written, keeping
+// its permissions. The rest of the file is kept, but comments are not
+fn edit_config(
+    path: PathBuf,
+    edit: impl FnOnce(&mut serde_yaml::Mapping) -> Result<(), String>,
+) -> Result<PathBuf, Error> {
+    let error = |e: &dyn std::fmt::Display| Error::Config(format!("{}: {}", path.display(), e));
+
+    let mut yaml = match std::fs::read_to_string(&path) {
+        Ok(text) if !text.trim().is_empty() => {
+            serde_yaml::from_str(&text).map_err(|e| error(&e))?
+        }
+        Ok(_) => serde_yaml::Mapping::new(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_yaml::Mapping::new(),
+        Err(e) => return Err(error(&e)),
+    };
+
+    edit(&mut yaml).map_err(|e| error(&e))?;
+    let text = serde_yaml::to_string(&yaml).map_err(|e| error(&e))?;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| error(&e))?;
+    }
+    let temp = path.with_extension("yaml.tmp");
+    std::fs::write(&temp, text).map_err(|e| error(&e))?;
+    match std::fs::metadata(&path) {
+        Ok(metadata) => {
+            std::fs::set_permissions(&temp, metadata.permissions()).map_err(|e| error(&e))?
+        }
+        // New files may hold the API key, so only the user can read them
+        #[cfg(unix)]
+        Err(_) => {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&temp, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| error(&e))?
+        }
+        #[cfg(not(unix))]
+        Err(_) => {}
+    }
+    std::fs::rename(&temp, &path).map_err(|e| error(&e))?;
+
+    Ok(path)
+}
+
+// A value given on the command line: numbers and booleans as such, other
+// values as strings. Numbers with leading zeros, which YAML would shorten,
+// stay strings
+pub fn parse_value(value: &str) -> serde_yaml::Value {
+    let digits = value.trim_start_matches('-');
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+
+    match (value, value.parse::<f64>()) {
+        ("true" | "false", _) => serde_yaml::Value::Bool(value == "true"),
+        (_, Ok(_)) if !leading_zero => serde_yaml::from_str(value).unwrap_or_else(|_| value.into()),
+        _ => value.into(),
+    }
+}
+
+// Sets each dotted key, e.g. `units.wind`, creating sections as needed. The
+// file is only written if the result is a valid configuration
+pub fn set(config: &Config, values: &[(&str, serde_yaml::Value)]) -> Result<PathBuf, Error> {
+    edit_config(config.edit_path(), |yaml| {
+        for (key, value) in values {
+            let mut parts: Vec<&str> = key.split('.').collect();
+            let last = parts.pop().unwrap_or_default();
+
+            let mut section = &mut *yaml;
+            for part in parts {
+                if let Some(units) = section.get_mut("units").filter(|_| part == "units") {
+                    *units = units_section(units);
+                }
+                section = section
+                    .entry(part.into())
+                    .or_insert_with(|| serde_yaml::Mapping::new().into())
+                    .as_mapping_mut()
+                    .ok_or_else(|| format!("`{}` is not a section", part))?;
+            }
+            // Null removes the key, e.g. a plain `key` once it is kept elsewhere
+            match value {
+                serde_yaml::Value::Null => section.remove(last),
+                value => section.insert(last.into(), value.clone()),
+            };
+        }
+
+        let text = serde_yaml::to_string(yaml).map_err(|e| e.to_string())?;
+        parse_layer(&text).map(|_| ())
+    })
+}
+
+fn locations(yaml: &mut serde_yaml::Mapping) -> Result<&mut serde_yaml::Mapping, String> {
+    yaml.entry("locations".into())
+        .or_insert_with(|| serde_yaml::Mapping::new().into())
+        .as_mapping_mut()
+        .ok_or_else(|| "`locations` is not a map".to_string())
+}
+
+// Adds or replaces `name` under `locations`, optionally making it the default
+pub fn save_location(
+    config: &Config,
+    name: &str,
+    location: &Location,
+    default: bool,
+) -> Result<PathBuf, Error> {
+    location.validate(name)?;
+    let value = serde_yaml::to_value(location).map_err(|e| Error::Config(e.to_string()))?;
+
+    edit_config(config.edit_path(), |yaml| {
+        locations(yaml)?.insert(name.into(), value);
+        if default {
+            yaml.insert("default_location".into(), name.into());
+        }
+        Ok(())
+    })
+}
+
+// Removes `name` from `locations`, and `default_location` if it names it
+pub fn remove_location(name: &str, config: &Config) -> Result<PathBuf, Error> {
+    let exists = config
+        .locations
+        .as_ref()
+        .is_some_and(|locations| locations.contains_key(name));
+    if !exists {
+        return Err(Error::UnknownLocation(name.to_string()));
+    }
+
+    // A location from another layer, e.g. a project's .owcli.yaml, stays in
+    // effect whatever is removed from the file being edited
+    let path = config.edit_path();
+    let defined = read_layer(&path).is_ok_and(|layer| {
+        layer
+            .get("locations")
+            .and_then(|locations| locations.as_mapping())
+            .is_some_and(|locations| locations.contains_key(name))
+    });
+    if !defined {
+        let origin = config
+            .origins
+            .get(&format!("locations.{}", name))
+            .cloned()
+            .unwrap_or_else(|| "another configuration file".to_string());
+        return Err(Error::Config(format!(
+            "{}: {} is defined in {}, remove it there",
+            path.display(),
+            name,
+            origin
+        )));
+    }
+
+    edit_config(path, |yaml| {
+        locations(yaml)?.remove(name);
+        if yaml.get("default_location").and_then(|v| v.as_str()) == Some(name) {
+            yaml.remove("default_location");
+        }
+        Ok(())
+    })
 }
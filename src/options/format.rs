@@ -0,0 +1,59 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Yaml,
+    Csv,
+    Tsv,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Summary {
+    Short,
+    #[default]
+    Full,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Output {
+    Waybar,
+    I3blocks,
+    Polybar,
+}
+
+// Zone that timestamps are shown in
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Tz {
+    #[default]
+    Location, // UTC offset of the location reported by the API
+    Local,
+    Utc,
+}
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Icons {
+    #[default]
+    Emoji,
+    Nerd,
+    Ascii,
+    None,
+}
+
+// How wind directions are shown
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WindDir {
+    #[default]
+    Deg,
+    Compass8,
+    Compass16,
+    Compass32,
+    Arrow,     // Pointing the way the wind blows to, as on weather maps
+    ArrowFrom, // Pointing the way it comes from
+}
@@ -0,0 +1,238 @@
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+
+use crate::{
+    client::{client::OpenWeatherClient, error::Error},
+    data::geocoding::{list, Geocoding, GeocodingData},
+};
+
+use super::{
+    args::Args,
+    config::{set, Config, Location, UnitsConfig},
+    key,
+    options::{get_base_url, get_key, get_units},
+    units::UnitSystem,
+};
+
+// Asks a question on stderr and reads the answer from stdin. The end of input
+// aborts the setup
+pub fn ask(question: &str) -> Result<String, Error> {
+    eprint!("{}", question);
+
+    let mut answer = String::new();
+    match std::io::stdin().read_line(&mut answer) {
+        Ok(0) | Err(_) => Err(Error::Aborted("no answer given".to_string())),
+        Ok(_) => Ok(answer.trim().to_string()),
+    }
+}
+
+// Asks for a secret without echoing it on a terminal. Piped input, e.g.
+// `pass show owm | owcli config set-key`, is read as is
+pub fn ask_secret(question: &str) -> Result<String, Error> {
+    use std::io::IsTerminal;
+
+    match std::io::stdin().is_terminal() {
+        true => rpassword::prompt_password(question)
+            .map(|answer| answer.trim().to_string())
+            .map_err(|e| Error::Aborted(e.to_string())),
+        false => ask(question),
+    }
+}
+
+fn client(key: &str, args: &Args, config: &Config) -> OpenWeatherClient {
+    match get_base_url(args, config) {
+        Some(base_url) => OpenWeatherClient::new(key).with_base_url(&base_url),
+        None => OpenWeatherClient::new(key),
+    }
+}
+
+// Asks for keys until one is accepted by the API. An empty answer keeps the
+// current key, wherever it comes from, and only a new key is returned to be
+// saved
+async fn ask_key(args: &Args, config: &Config) -> Result<(String, bool), Error> {
+    let current = get_key(args, config).ok().flatten();
+
+    loop {
+        let (key, new) = match (ask_secret("OpenWeather API key: ")?, &current) {
+            (key, Some(current)) if key.is_empty() => (current.to_owned(), false),
+            (key, None) if key.is_empty() => continue,
+            (key, _) => (key, true),
+        };
+
+        let query = [("lat", "0".to_string()), ("lon", "0".to_string())];
+        match client(&key, args, config)
+            .get::<serde_json::Value>("/data/2.5/weather", &query)
+            .await
+        {
+            Ok(_) => return Ok((key, new)),
+            Err(Error::Unauthorized(message)) => eprintln!(
+                "The API key was rejected: {}\nNew keys can take up to 2 hours to become active",
+                message
+            ),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Asks for a place until one is found, or none is wanted
+async fn ask_home(client: &OpenWeatherClient) -> Result<Option<GeocodingData>, Error> {
+    loop {
+        let name = ask("Home city, e.g. Berlin or Springfield,IL,US (empty to skip): ")?;
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        let mut matches = Geocoding::search(client, &name).await?;
+        match matches.len() {
+            0 => eprintln!("No place named {} was found", name),
+            1 => return Ok(matches.pop()),
+            count => {
+                eprintln!("Several places match:\n{}", list(&matches));
+                loop {
+                    match ask(&format!("Pick one [1-{}]: ", count))?.parse::<usize>() {
+                        Ok(pick) if (1..=count).contains(&pick) => {
+                            return Ok(Some(matches.swap_remove(pick - 1)))
+                        }
+                        _ => continue,
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Where a new key is kept. The configuration file comes last, as anyone who
+// can read it, or a copy of it, sees the key
+enum Storage {
+    File(PathBuf),
+    Keyring,
+    Config,
+}
+
+fn ask_storage(config: &Config) -> Result<Storage, Error> {
+    let file = config
+        .key_file
+        .as_deref()
+        .map(key::expand)
+        .unwrap_or_else(key::default_file);
+    let mut choices = vec![(
+        format!("in {}, which only you can read", file.display()),
+        Storage::File(file),
+    )];
+    if cfg!(feature = "keyring") {
+        choices.push(("in the system keyring".to_string(), Storage::Keyring));
+    }
+    choices.push((
+        "in the configuration file, as plain text".to_string(),
+        Storage::Config,
+    ));
+
+    eprintln!("Where should the key be kept?");
+    for (n, (label, _)) in choices.iter().enumerate() {
+        eprintln!("  {}. {}", n + 1, label);
+    }
+    let question = format!("Pick one [1-{}, default 1]: ", choices.len());
+    loop {
+        let pick = match ask(&question)?.as_str() {
+            "" => 1,
+            answer => answer.parse::<usize>().unwrap_or_default(),
+        };
+        if (1..=choices.len()).contains(&pick) {
+            return Ok(choices.swap_remove(pick - 1).1);
+        }
+    }
+}
+
+fn ask_units(args: &Args, config: &Config) -> Result<UnitSystem, Error> {
+    let current = get_units(args, config).unwrap_or_default();
+    let question = format!(
+        "Units, metric, imperial or standard [{}]: ",
+        current
+            .to_possible_value()
+            .expect("Units have names")
+            .get_name()
+    );
+
+    loop {
+        match ask(&question)?.as_str() {
+            "" => return Ok(current),
+            answer => match UnitSystem::from_str(answer, true) {
+                Ok(units) => return Ok(units),
+                Err(_) => continue,
+            },
+        }
+    }
+}
+
+// `owcli config init`: asks for an API key, checked against the API, where to
+// keep it, a home city to geocode and the unit system, and saves them to the
+// configuration file. The home city becomes the `home` location and
+// `default_location`
+pub async fn init(args: &Args, config: &Config) -> Result<PathBuf, Error> {
+    let (key, new) = ask_key(args, config).await?;
+    let storage = match new {
+        true => Some(ask_storage(config)?),
+        false => None,
+    };
+    let home = ask_home(&client(&key, args, config)).await?;
+    let units = ask_units(args, config)?;
+
+    // A units section keeps its per-quantity overrides
+    let units_key = match config.units {
+        Some(UnitsConfig::Section(_)) => "units.system",
+        _ => "units",
+    };
+    let mut values = vec![(
+        units_key,
+        units
+            .to_possible_value()
+            .expect("Units have names")
+            .get_name()
+            .into(),
+    )];
+
+    // Sources that take precedence over the chosen one are removed from the
+    // file, so that the new key is the one used
+    let null = serde_yaml::Value::Null;
+    match &storage {
+        Some(Storage::File(path)) => {
+            key::save_file(path, &key)?;
+            values.push(("key", null.clone()));
+            values.push(("key_command", null));
+            values.push(("key_file", path.display().to_string().into()));
+        }
+        Some(Storage::Keyring) => {
+            key::store(&key)?;
+            values.push(("key", null.clone()));
+            values.push(("key_command", null.clone()));
+            values.push(("key_file", null));
+        }
+        Some(Storage::Config) => values.push(("key", key.into())),
+        None => {}
+    }
+
+    if let Some(home) = home {
+        let location = Location {
+            lat: home.lat,
+            lon: home.lon,
+            name: home.name.clone(),
+            state: home.state.clone(),
+            country: home.country.clone(),
+            ..Location::default()
+        };
+        let location = serde_yaml::to_value(location).map_err(|e| Error::Config(e.to_string()))?;
+        values.push(("locations.home", location));
+        values.push(("default_location", "home".into()));
+    }
+
+    let path = set(config, &values)?;
+    if let Some(Storage::Config) = storage {
+        key::restrict(&path).map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))?;
+        eprintln!(
+            "The key is stored in plain text in {}, which only you can read",
+            path.display()
+        );
+    }
+    Ok(path)
+}
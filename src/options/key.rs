@@ -0,0 +1,180 @@
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::client::error::Error;
+
+// Secret stores such as `pass` print the secret on the first line, followed
+// by optional notes
+fn first_line(text: &str) -> Option<String> {
+    text.lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+}
+
+// `key_command`, e.g. `pass show owm`, run through the shell. Its stdout is the
+// key, while stdin and stderr stay attached so that it can ask for a passphrase
+pub fn from_command(command: &str) -> Result<String, Error> {
+    #[cfg(unix)]
+    let mut shell = Command::new("sh");
+    #[cfg(unix)]
+    shell.arg("-c");
+    #[cfg(windows)]
+    let mut shell = Command::new("cmd");
+    #[cfg(windows)]
+    shell.arg("/C");
+
+    let output = shell
+        .arg(command)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| Error::KeySource(format!("key_command `{}`: {}", command, e)))?;
+
+    if !output.status.success() {
+        return Err(Error::KeySource(format!(
+            "key_command `{}` failed with {}",
+            command, output.status
+        )));
+    }
+
+    first_line(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| Error::KeySource(format!("key_command `{}` printed no key", command)))
+}
+
+// A leading `~/` stands for the home directory, as in a shell
+pub fn expand(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+// `key_file`, which like an SSH key must not be readable by other users
+pub fn from_file(path: &Path) -> Result<String, Error> {
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| Error::KeySource(format!("key_file {}: {}", path.display(), e)))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o077 != 0 {
+            return Err(Error::KeySource(format!(
+                "key_file {path} can be read by other users, run `chmod 600 {path}`",
+                path = path.display()
+            )));
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = metadata;
+
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| Error::KeySource(format!("key_file {}: {}", path.display(), e)))?;
+    first_line(&text)
+        .ok_or_else(|| Error::KeySource(format!("key_file {} is empty", path.display())))
+}
+
+// Where `owcli config init` keeps a new key by default, next to the
+// configuration file
+pub fn default_file() -> PathBuf {
+    super::config::Config::path().with_file_name("key")
+}
+
+// Only the owner may read or write a file holding the key
+pub fn restrict(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+// Writes the key to a file that is private from the start
+pub fn save_file(path: &Path, key: &str) -> Result<(), Error> {
+    let error = |e: std::io::Error| Error::Config(format!("{}: {}", path.display(), e));
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(error)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(path).map_err(error)?;
+    restrict(path).map_err(error)?;
+    std::io::Write::write_all(&mut file, format!("{}\n", key).as_bytes()).map_err(error)
+}
+
+// The key is stored in the system keyring (the Secret Service on Linux) under
+// this service and user. The Secret Service client starts its own runtime, so
+// it runs on a thread outside of ours
+#[cfg(feature = "keyring")]
+fn with_entry<T: Send>(
+    f: impl FnOnce(keyring::Entry) -> keyring::Result<T> + Send,
+) -> Result<keyring::Result<T>, Error> {
+    let entry =
+        keyring::Entry::new("owcli", "api-key").map_err(|e| Error::Keyring(e.to_string()))?;
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| f(entry))
+            .join()
+            .map_err(|_| Error::Keyring("the keyring client panicked".to_string()))
+    })
+}
+
+// Why the keyring could not be reached, kept for when no key is found at all
+static UNAVAILABLE: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+// A missing entry is not an error, the key may simply not be set up yet. A
+// keyring that cannot be reached, e.g. on a headless machine without a Secret
+// Service, is skipped the same way, so that it only matters when the key is
+// needed and no other source has it
+#[cfg(feature = "keyring")]
+pub fn from_keyring() -> Result<Option<String>, Error> {
+    match with_entry(|entry| entry.get_password())? {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e @ (keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_))) => {
+            let _ = UNAVAILABLE.set(e.to_string());
+            Ok(None)
+        }
+        Err(e) => Err(Error::KeySource(format!("keyring: {}", e))),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn from_keyring() -> Result<Option<String>, Error> {
+    Ok(None)
+}
+
+// The error for a run that needs a key but found none
+pub fn missing() -> Error {
+    match UNAVAILABLE.get() {
+        Some(reason) => Error::KeySource(format!(
+            "no key is set and the keyring is unavailable: {}",
+            reason
+        )),
+        None => Error::MissingKey,
+    }
+}
+
+// `owcli config set-key`
+#[cfg(feature = "keyring")]
+pub fn store(key: &str) -> Result<(), Error> {
+    with_entry(|entry| entry.set_password(key))?.map_err(|e| Error::Keyring(e.to_string()))
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn store(_key: &str) -> Result<(), Error> {
+    Err(Error::Keyring(
+        "this build has no keyring support, rebuild with `--features keyring` \
+         or use `key_command` or `key_file` instead"
+            .to_string(),
+    ))
+}
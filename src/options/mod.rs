@@ -1,3 +1,9 @@
 pub mod args;
 pub mod config;
+pub mod format;
+pub mod init;
+pub mod key;
+#[allow(clippy::module_inception)]
 pub mod options;
+pub mod settings;
+pub mod units;
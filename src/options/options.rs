@@ -1,65 +1,658 @@
-use super::{args::Args, config::Config};
+use std::{collections::HashSet, path::PathBuf, sync::OnceLock};
 
-pub fn get_key(args: &Args, config: &Config) -> Option<String> {
-    match (&args.key, &config.key) {
-        (Some(key), _) => Some(key.to_string()),
-        (_, Some(key)) => Some(key.to_string()),
-        _ => None,
+use clap::ValueEnum;
+
+use crate::client::error::Error;
+
+use super::{
+    args::Args,
+    config::Config,
+    format::{Icons, Tz, WindDir},
+    key,
+    units::{DistanceUnit, PrecipUnit, PressureUnit, TempUnit, UnitSystem, Units, WindUnit},
+};
+
+// Variables that a .env file set, and the file, kept for `config show --origin`
+static DOTENV: OnceLock<(PathBuf, HashSet<String>)> = OnceLock::new();
+
+// Variables from a .env file in the current directory or a parent, which do
+// not replace those already set
+pub fn load_dotenv() {
+    let set: HashSet<String> = std::env::vars_os()
+        .filter_map(|(name, _)| name.into_string().ok())
+        .collect();
+    let Ok(path) = dotenv::dotenv() else {
+        return;
+    };
+
+    // Deprecated, but the only way dotenv lists the variables of a file
+    #[allow(deprecated)]
+    let loaded = dotenv::from_path_iter(&path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|(name, _)| name)
+        .filter(|name| !set.contains(name))
+        .collect();
+    let _ = DOTENV.set((path, loaded));
+}
+
+// The variable, and the .env file if it was set from one
+fn env_origin(name: &str) -> String {
+    match DOTENV.get() {
+        Some((path, names)) if names.contains(name) => {
+            format!("{} in {}", name, path.display())
+        }
+        _ => name.to_string(),
     }
 }
 
+// Environment variables sit between the command line and the config file.
+// Empty variables count as unset
+pub fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+// A variable naming one of the values of a flag, e.g. OWCLI_UNITS=imperial
+fn env_value<T: ValueEnum>(name: &str) -> Option<T> {
+    T::from_str(&env(name)?, true).ok()
+}
+
+// The location from the environment gives way to locations chosen with
+// --location or --locations-file
+fn env_location(args: &Args, name: &str) -> Option<String> {
+    match args.location.is_empty() && args.locations_file.is_none() {
+        true => env(name),
+        false => None,
+    }
+}
+
+// Variables that are checked before use, so that a typo fails like the flag
+// would rather than being ignored
+type Check = fn(&str) -> bool;
+
+const ENV_VALUES: &[(&str, Check)] = &[
+    ("OWCLI_LAT", |v| v.parse::<f64>().is_ok()),
+    ("OWCLI_LON", |v| v.parse::<f64>().is_ok()),
+    ("OWCLI_UNITS", |v| UnitSystem::from_str(v, true).is_ok()),
+    ("OWCLI_TEMP_UNIT", |v| TempUnit::from_str(v, true).is_ok()),
+    ("OWCLI_WIND_UNIT", |v| WindUnit::from_str(v, true).is_ok()),
+    ("OWCLI_PRESSURE_UNIT", |v| {
+        PressureUnit::from_str(v, true).is_ok()
+    }),
+    ("OWCLI_DISTANCE_UNIT", |v| {
+        DistanceUnit::from_str(v, true).is_ok()
+    }),
+    ("OWCLI_PRECIP_UNIT", |v| {
+        PrecipUnit::from_str(v, true).is_ok()
+    }),
+    ("OWCLI_WIND_DIR_FORMAT", |v| {
+        WindDir::from_str(v, true).is_ok()
+    }),
+    ("OWCLI_ICONS", |v| Icons::from_str(v, true).is_ok()),
+    ("OWCLI_TZ", |v| Tz::from_str(v, true).is_ok()),
+];
+
+pub fn check_env() -> Result<(), Error> {
+    for (name, valid) in ENV_VALUES {
+        if let Some(value) = env(name) {
+            if !valid(&value) {
+                return Err(Error::InvalidEnv(format!("{}={}", name, value)));
+            }
+        }
+    }
+    Ok(())
+}
+
+// Whether the command line or the environment gives a location, which then
+// takes precedence over `default_location`
+pub fn has_location(args: &Args) -> bool {
+    let flags =
+        args.lat.is_some() || args.lon.is_some() || args.city.is_some() || args.zip.is_some();
+    let env = ["OWCLI_LAT", "OWCLI_LON", "OWCLI_CITY", "OWCLI_ZIP"]
+        .iter()
+        .any(|name| env_location(args, name).is_some());
+
+    flags || env
+}
+
+// The key with where it came from. OWCLI_KEY takes precedence over
+// OWM_API_KEY, the name other OpenWeather tools use, and a plain `key` in the
+// configuration over `key_command`, `key_file` and the keyring, which are only
+// consulted when needed
+fn find_key(args: &Args, config: &Config) -> Result<Option<(String, String)>, Error> {
+    let env = ["OWCLI_KEY", "OWM_API_KEY"]
+        .into_iter()
+        .find_map(|name| Some((env(name)?, name)));
+    let file = |name: &str| {
+        config
+            .origins
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| "configuration file".to_string())
+    };
+
+    let found = match (&args.key, env, &config.key) {
+        (Some(key), _, _) => Some((key.to_string(), "--key".to_string())),
+        (_, Some((key, name)), _) => Some((key, env_origin(name))),
+        (_, _, Some(key)) => Some((key.to_string(), file("key"))),
+        _ => match (&config.key_command, &config.key_file) {
+            (Some(command), _) => Some((
+                key::from_command(command)?,
+                format!("key_command in {}", file("key_command")),
+            )),
+            (_, Some(path)) => Some((
+                key::from_file(&key::expand(path))?,
+                format!("key_file in {}", file("key_file")),
+            )),
+            _ => key::from_keyring()?.map(|key| (key, "keyring".to_string())),
+        },
+    };
+
+    Ok(found)
+}
+
+pub fn get_key(args: &Args, config: &Config) -> Result<Option<String>, Error> {
+    Ok(find_key(args, config)?.map(|(key, _)| key))
+}
+
 pub fn get_lat(args: &Args, config: &Config) -> Option<f64> {
-    match (&args.lat, &config.lat) {
-        (Some(lat), _) => Some(*lat),
-        (_, Some(lat)) => Some(lat.parse().expect("Could not parse latitude as f64!")),
+    let env = env_location(args, "OWCLI_LAT").and_then(|lat| lat.parse().ok());
+
+    match (&args.lat, env, &config.lat) {
+        (Some(lat), _, _) => Some(*lat),
+        (_, Some(lat), _) => Some(lat),
+        (_, _, Some(lat)) => Some(lat.parse().expect("Could not parse latitude as f64!")),
         _ => None,
     }
 }
 
 pub fn get_lon(args: &Args, config: &Config) -> Option<f64> {
-    match (&args.lat, &config.lat) {
-        (Some(lon), _) => Some(*lon),
-        (_, Some(lon)) => Some(lon.parse().expect("Could not parse longitude as f64!")),
+    let env = env_location(args, "OWCLI_LON").and_then(|lon| lon.parse().ok());
+
+    match (&args.lon, env, &config.lon) {
+        (Some(lon), _, _) => Some(*lon),
+        (_, Some(lon), _) => Some(lon),
+        (_, _, Some(lon)) => Some(lon.parse().expect("Could not parse longitude as f64!")),
         _ => None,
     }
 }
 
 pub fn get_city(args: &Args, config: &Config) -> Option<String> {
-    match (&args.city, &config.city) {
-        (Some(city), _) => Some(city.to_string()),
-        (_, Some(city)) => Some(city.to_string()),
+    let env = env_location(args, "OWCLI_CITY");
+
+    match (&args.city, &env, &config.city) {
+        (Some(city), _, _) => Some(city.to_string()),
+        (_, Some(city), _) => Some(city.to_string()),
+        (_, _, Some(city)) => Some(city.to_string()),
         _ => None,
     }
 }
 
 pub fn get_state(args: &Args, config: &Config) -> Option<String> {
-    match (&args.state, &config.state) {
-        (Some(state), _) => Some(state.to_string()),
-        (_, Some(state)) => Some(state.to_string()),
+    let env = env_location(args, "OWCLI_STATE");
+
+    match (&args.state, &env, &config.state) {
+        (Some(state), _, _) => Some(state.to_string()),
+        (_, Some(state), _) => Some(state.to_string()),
+        (_, _, Some(state)) => Some(state.to_string()),
         _ => None,
     }
 }
 
 pub fn get_country(args: &Args, config: &Config) -> Option<String> {
-    match (&args.country, &config.country) {
-        (Some(country), _) => Some(country.to_string()),
-        (_, Some(country)) => Some(country.to_string()),
+    let env = env_location(args, "OWCLI_COUNTRY");
+
+    match (&args.country, &env, &config.country) {
+        (Some(country), _, _) => Some(country.to_string()),
+        (_, Some(country), _) => Some(country.to_string()),
+        (_, _, Some(country)) => Some(country.to_string()),
         _ => None,
     }
 }
 
 pub fn get_zip(args: &Args, config: &Config) -> Option<String> {
-    match (&args.zip, &config.zip) {
-        (Some(zip), _) => Some(zip.to_string()),
-        (_, Some(zip)) => Some(zip.to_string()),
+    let env = env_location(args, "OWCLI_ZIP");
+
+    match (&args.zip, &env, &config.zip) {
+        (Some(zip), _, _) => Some(zip.to_string()),
+        (_, Some(zip), _) => Some(zip.to_string()),
+        (_, _, Some(zip)) => Some(zip.to_string()),
+        _ => None,
+    }
+}
+
+pub fn get_units(args: &Args, config: &Config) -> Option<UnitSystem> {
+    let section = config.units.as_ref().map(|units| units.section());
+
+    match (
+        &args.units,
+        env_value("OWCLI_UNITS"),
+        section.and_then(|s| s.system),
+    ) {
+        (Some(units), _, _) => Some(*units),
+        (_, Some(units), _) => Some(units),
+        (_, _, Some(units)) => Some(units),
         _ => None,
     }
 }
 
-pub fn get_units(args: &Args, config: &Config) -> Option<String> {
-    match (&args.units, &config.units) {
-        (Some(units), _) => Some(units.to_string()),
-        (_, Some(units)) => Some(units.to_string()),
+pub fn get_temp_unit(args: &Args, config: &Config) -> Option<TempUnit> {
+    let section = config.units.as_ref().map(|units| units.section());
+
+    let env = env_value("OWCLI_TEMP_UNIT");
+
+    match (&args.temp_unit, env, section.and_then(|s| s.temp)) {
+        (Some(unit), _, _) => Some(*unit),
+        (_, Some(unit), _) => Some(unit),
+        (_, _, Some(unit)) => Some(unit),
         _ => None,
     }
 }
+
+pub fn get_wind_unit(args: &Args, config: &Config) -> Option<WindUnit> {
+    let section = config.units.as_ref().map(|units| units.section());
+
+    let env = env_value("OWCLI_WIND_UNIT");
+
+    match (&args.wind_unit, env, section.and_then(|s| s.wind)) {
+        (Some(unit), _, _) => Some(*unit),
+        (_, Some(unit), _) => Some(unit),
+        (_, _, Some(unit)) => Some(unit),
+        _ => None,
+    }
+}
+
+pub fn get_pressure_unit(args: &Args, config: &Config) -> Option<PressureUnit> {
+    let section = config.units.as_ref().map(|units| units.section());
+
+    let env = env_value("OWCLI_PRESSURE_UNIT");
+
+    match (&args.pressure_unit, env, section.and_then(|s| s.pressure)) {
+        (Some(unit), _, _) => Some(*unit),
+        (_, Some(unit), _) => Some(unit),
+        (_, _, Some(unit)) => Some(unit),
+        _ => None,
+    }
+}
+
+pub fn get_distance_unit(args: &Args, config: &Config) -> Option<DistanceUnit> {
+    let section = config.units.as_ref().map(|units| units.section());
+
+    let env = env_value("OWCLI_DISTANCE_UNIT");
+
+    match (&args.distance_unit, env, section.and_then(|s| s.distance)) {
+        (Some(unit), _, _) => Some(*unit),
+        (_, Some(unit), _) => Some(unit),
+        (_, _, Some(unit)) => Some(unit),
+        _ => None,
+    }
+}
+
+pub fn get_precip_unit(args: &Args, config: &Config) -> Option<PrecipUnit> {
+    let section = config.units.as_ref().map(|units| units.section());
+
+    let env = env_value("OWCLI_PRECIP_UNIT");
+
+    match (&args.precip_unit, env, section.and_then(|s| s.precip)) {
+        (Some(unit), _, _) => Some(*unit),
+        (_, Some(unit), _) => Some(unit),
+        (_, _, Some(unit)) => Some(unit),
+        _ => None,
+    }
+}
+
+// The unit system's units, with any per-quantity overrides applied
+pub fn get_display_units(args: &Args, config: &Config) -> Units {
+    let units = Units::from(get_units(args, config).unwrap_or_default());
+
+    Units {
+        temp: get_temp_unit(args, config).unwrap_or(units.temp),
+        wind: get_wind_unit(args, config).unwrap_or(units.wind),
+        pressure: get_pressure_unit(args, config).unwrap_or(units.pressure),
+        distance: get_distance_unit(args, config).unwrap_or(units.distance),
+        precip: get_precip_unit(args, config).unwrap_or(units.precip),
+        ..units
+    }
+}
+
+// Also lets test harnesses redirect the binary without touching the flags or
+// the config file
+pub fn get_base_url(args: &Args, config: &Config) -> Option<String> {
+    let env = env("OWCLI_BASE_URL");
+
+    match (&args.base_url, &env, &config.base_url) {
+        (Some(base_url), _, _) => Some(base_url.to_string()),
+        (_, Some(base_url), _) => Some(base_url.to_string()),
+        (_, _, Some(base_url)) => Some(base_url.to_string()),
+        _ => None,
+    }
+}
+
+// --template may name one of the templates in the config file, otherwise it
+// is used as the template itself
+pub fn get_template(args: &Args, config: &Config) -> Option<String> {
+    let template = args.template.as_ref()?;
+
+    match config.templates.as_ref().and_then(|t| t.get(template)) {
+        Some(template) => Some(template.to_string()),
+        None => Some(template.to_string()),
+    }
+}
+
+pub fn get_icons(args: &Args, config: &Config) -> Option<Icons> {
+    match (&args.icons, env_value("OWCLI_ICONS"), &config.icons) {
+        (Some(icons), _, _) => Some(*icons),
+        (_, Some(icons), _) => Some(icons),
+        (_, _, Some(icons)) => Some(*icons),
+        _ => None,
+    }
+}
+
+pub fn get_tz(args: &Args, config: &Config) -> Option<Tz> {
+    match (&args.tz, env_value("OWCLI_TZ"), &config.tz) {
+        (Some(tz), _, _) => Some(*tz),
+        (_, Some(tz), _) => Some(tz),
+        (_, _, Some(tz)) => Some(*tz),
+        _ => None,
+    }
+}
+
+pub fn get_time_format(args: &Args, config: &Config) -> Option<String> {
+    let env = env("OWCLI_TIME_FORMAT");
+
+    match (&args.time_format, &env, &config.time_format) {
+        (Some(format), _, _) => Some(format.to_string()),
+        (_, Some(format), _) => Some(format.to_string()),
+        (_, _, Some(format)) => Some(format.to_string()),
+        _ => None,
+    }
+}
+
+pub fn get_datetime_format(args: &Args, config: &Config) -> Option<String> {
+    let env = env("OWCLI_DATETIME_FORMAT");
+
+    match (&args.datetime_format, &env, &config.datetime_format) {
+        (Some(format), _, _) => Some(format.to_string()),
+        (_, Some(format), _) => Some(format.to_string()),
+        (_, _, Some(format)) => Some(format.to_string()),
+        _ => None,
+    }
+}
+
+pub fn get_wind_dir_format(args: &Args, config: &Config) -> Option<WindDir> {
+    let env = env_value("OWCLI_WIND_DIR_FORMAT");
+
+    match (&args.wind_dir_format, env, &config.wind_dir_format) {
+        (Some(format), _, _) => Some(*format),
+        (_, Some(format), _) => Some(format),
+        (_, _, Some(format)) => Some(*format),
+        _ => None,
+    }
+}
+
+pub fn get_lang(args: &Args, config: &Config) -> Option<String> {
+    let env = env("OWCLI_LANG");
+
+    match (&args.lang, &env, &config.lang) {
+        (Some(lang), _, _) => Some(lang.to_string()),
+        (_, Some(lang), _) => Some(lang.to_string()),
+        (_, _, Some(lang)) => Some(lang.to_string()),
+        _ => None,
+    }
+}
+
+// A setting in effect and where it came from: a flag, an environment
+// variable, a configuration file, a saved location or the default
+pub struct Setting {
+    pub name: &'static str,
+    pub value: String,
+    pub origin: String,
+}
+
+fn name<T: ValueEnum>(value: T) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+// Only the end of the key is shown, enough to tell keys apart
+fn mask(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    match chars.len() {
+        0..=8 => "*".repeat(chars.len()),
+        len => "*".repeat(len - 4) + &chars[len - 4..].iter().collect::<String>(),
+    }
+}
+
+// A row of `owcli config show`: the value in effect, whether a flag, an
+// environment variable or the configuration sets it, and the default
+#[derive(Default)]
+struct Row {
+    name: &'static str,
+    value: Option<String>,
+    flag: bool,
+    env: Option<&'static str>,
+    file: bool,
+    default: Option<String>,
+}
+
+// The settings that apply to this run, for `owcli config show`. Unset
+// settings are left out, except for those with a default. Finding the key may
+// run `key_command`, which can fail
+pub fn settings(args: &Args, config: &Config) -> Result<Vec<Setting>, Error> {
+    let section = config
+        .units
+        .as_ref()
+        .map(|units| units.section())
+        .unwrap_or_default();
+    let location = |name: &str| env_location(args, name);
+
+    let rows = vec![
+        Row {
+            name: "key_command",
+            value: config.key_command.clone(),
+            file: config.key_command.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "key_file",
+            value: config.key_file.clone(),
+            file: config.key_file.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "units",
+            value: get_units(args, config).map(name),
+            flag: args.units.is_some(),
+            env: env_value::<UnitSystem>("OWCLI_UNITS").map(|_| "OWCLI_UNITS"),
+            file: section.system.is_some(),
+            default: Some(name(UnitSystem::default())),
+        },
+        Row {
+            name: "temp_unit",
+            value: get_temp_unit(args, config).map(name),
+            flag: args.temp_unit.is_some(),
+            env: env_value::<TempUnit>("OWCLI_TEMP_UNIT").map(|_| "OWCLI_TEMP_UNIT"),
+            file: section.temp.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "wind_unit",
+            value: get_wind_unit(args, config).map(name),
+            flag: args.wind_unit.is_some(),
+            env: env_value::<WindUnit>("OWCLI_WIND_UNIT").map(|_| "OWCLI_WIND_UNIT"),
+            file: section.wind.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "pressure_unit",
+            value: get_pressure_unit(args, config).map(name),
+            flag: args.pressure_unit.is_some(),
+            env: env_value::<PressureUnit>("OWCLI_PRESSURE_UNIT").map(|_| "OWCLI_PRESSURE_UNIT"),
+            file: section.pressure.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "distance_unit",
+            value: get_distance_unit(args, config).map(name),
+            flag: args.distance_unit.is_some(),
+            env: env_value::<DistanceUnit>("OWCLI_DISTANCE_UNIT").map(|_| "OWCLI_DISTANCE_UNIT"),
+            file: section.distance.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "precip_unit",
+            value: get_precip_unit(args, config).map(name),
+            flag: args.precip_unit.is_some(),
+            env: env_value::<PrecipUnit>("OWCLI_PRECIP_UNIT").map(|_| "OWCLI_PRECIP_UNIT"),
+            file: section.precip.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "lat",
+            value: get_lat(args, config).map(|lat| lat.to_string()),
+            flag: args.lat.is_some(),
+            env: location("OWCLI_LAT").map(|_| "OWCLI_LAT"),
+            file: config.lat.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "lon",
+            value: get_lon(args, config).map(|lon| lon.to_string()),
+            flag: args.lon.is_some(),
+            env: location("OWCLI_LON").map(|_| "OWCLI_LON"),
+            file: config.lon.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "city",
+            value: get_city(args, config),
+            flag: args.city.is_some(),
+            env: location("OWCLI_CITY").map(|_| "OWCLI_CITY"),
+            file: config.city.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "state",
+            value: get_state(args, config),
+            flag: args.state.is_some(),
+            env: location("OWCLI_STATE").map(|_| "OWCLI_STATE"),
+            file: config.state.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "country",
+            value: get_country(args, config),
+            flag: args.country.is_some(),
+            env: location("OWCLI_COUNTRY").map(|_| "OWCLI_COUNTRY"),
+            file: config.country.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "zip",
+            value: get_zip(args, config),
+            flag: args.zip.is_some(),
+            env: location("OWCLI_ZIP").map(|_| "OWCLI_ZIP"),
+            file: config.zip.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "lang",
+            value: get_lang(args, config),
+            flag: args.lang.is_some(),
+            env: env("OWCLI_LANG").map(|_| "OWCLI_LANG"),
+            file: config.lang.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "tz",
+            value: get_tz(args, config).map(name),
+            flag: args.tz.is_some(),
+            env: env_value::<Tz>("OWCLI_TZ").map(|_| "OWCLI_TZ"),
+            file: config.tz.is_some(),
+            default: Some(name(Tz::default())),
+        },
+        Row {
+            name: "icons",
+            value: get_icons(args, config).map(name),
+            flag: args.icons.is_some(),
+            env: env_value::<Icons>("OWCLI_ICONS").map(|_| "OWCLI_ICONS"),
+            file: config.icons.is_some(),
+            default: Some(name(Icons::default())),
+        },
+        Row {
+            name: "wind_dir_format",
+            value: get_wind_dir_format(args, config).map(name),
+            flag: args.wind_dir_format.is_some(),
+            env: env_value::<WindDir>("OWCLI_WIND_DIR_FORMAT").map(|_| "OWCLI_WIND_DIR_FORMAT"),
+            file: config.wind_dir_format.is_some(),
+            default: Some(name(WindDir::default())),
+        },
+        Row {
+            name: "time_format",
+            value: get_time_format(args, config),
+            flag: args.time_format.is_some(),
+            env: env("OWCLI_TIME_FORMAT").map(|_| "OWCLI_TIME_FORMAT"),
+            file: config.time_format.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "datetime_format",
+            value: get_datetime_format(args, config),
+            flag: args.datetime_format.is_some(),
+            env: env("OWCLI_DATETIME_FORMAT").map(|_| "OWCLI_DATETIME_FORMAT"),
+            file: config.datetime_format.is_some(),
+            ..Row::default()
+        },
+        Row {
+            name: "base_url",
+            value: get_base_url(args, config),
+            flag: args.base_url.is_some(),
+            env: env("OWCLI_BASE_URL").map(|_| "OWCLI_BASE_URL"),
+            file: config.base_url.is_some(),
+            ..Row::default()
+        },
+    ];
+
+    let key = find_key(args, config)?.map(|(key, origin)| Setting {
+        name: "key",
+        value: mask(&key),
+        origin,
+    });
+
+    let rows = rows.into_iter().filter_map(|row| {
+        // Units live in the `units` section, whose entries may come from
+        // different files
+        let key = match (row.name, row.name.strip_suffix("_unit")) {
+            ("units", _) => "units.system".to_string(),
+            (_, Some(quantity)) => format!("units.{}", quantity),
+            _ => row.name.to_string(),
+        };
+        let origin = match (row.flag, row.env, row.file) {
+            (true, _, _) => format!("--{}", row.name.replace('_', "-")),
+            (_, Some(env), _) => env_origin(env),
+            (_, _, true) => config
+                .origins
+                .get(&key)
+                .or_else(|| {
+                    config
+                        .origins
+                        .get("units")
+                        .filter(|_| key.starts_with("units."))
+                })
+                .cloned()
+                .unwrap_or_else(|| "configuration file".to_string()),
+            _ => "default".to_string(),
+        };
+
+        Some(Setting {
+            name: row.name,
+            value: row.value.or(row.default)?,
+            origin,
+        })
+    });
+
+    Ok(key.into_iter().chain(rows).collect())
+}
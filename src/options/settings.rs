@@ -0,0 +1,102 @@
+use crate::{
+    client::error::Error,
+    data::{
+        convert::{
+            local_offset, localize_compass, parse_date, to_arrow, to_compass_points, to_datetime,
+        },
+        record::Value,
+    },
+};
+
+use super::{
+    args::Args,
+    config::Config,
+    format::{Icons, Tz, WindDir},
+    options::{
+        get_datetime_format, get_display_units, get_icons, get_lang, get_time_format, get_tz,
+        get_wind_dir_format,
+    },
+    units::Units,
+};
+
+const TIME_FORMAT: &str = "%H:%M";
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// Display settings resolved from the arguments and config, shared by the printers
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub units: Units,
+    // None unless set explicitly, in which case summaries show glyphs too
+    pub icons: Option<Icons>,
+    pub tz: Tz,
+    pub time_format: String,
+    pub datetime_format: String,
+    pub wind_dir: WindDir,
+    pub lang: Option<String>,
+}
+
+impl Settings {
+    pub fn new(args: &Args, config: &Config) -> Self {
+        Self {
+            units: get_display_units(args, config),
+            icons: get_icons(args, config),
+            tz: get_tz(args, config).unwrap_or_default(),
+            time_format: get_time_format(args, config).unwrap_or(TIME_FORMAT.to_string()),
+            datetime_format: get_datetime_format(args, config)
+                .unwrap_or(DATETIME_FORMAT.to_string()),
+            wind_dir: get_wind_dir_format(args, config).unwrap_or_default(),
+            lang: get_lang(args, config),
+        }
+    }
+
+    // The UTC offset to show times at, given the location's. None is the
+    // local zone
+    pub fn offset(&self, location: Option<i32>) -> Option<i32> {
+        match self.tz {
+            Tz::Location => Some(location.unwrap_or(0)),
+            Tz::Local => None,
+            Tz::Utc => Some(0),
+        }
+    }
+
+    // Date from the user -> Unix timestamp, reading it in the zone times are
+    // shown in
+    pub fn timestamp(&self, date: &str, location: Option<i32>) -> Result<i64, Error> {
+        let date = parse_date(date)?.and_utc().timestamp();
+        let offset = self.offset(location).unwrap_or_else(|| local_offset(date));
+        Ok(date - offset as i64)
+    }
+
+    // Unix timestamp -> time of day, e.g. for sunrise
+    pub fn time(&self, timestamp: i64, location: Option<i32>) -> String {
+        to_datetime(timestamp, self.offset(location), &self.time_format)
+    }
+
+    pub fn datetime(&self, timestamp: i64, location: Option<i32>) -> String {
+        to_datetime(timestamp, self.offset(location), &self.datetime_format)
+    }
+
+    pub fn date(&self, timestamp: i64, location: Option<i32>) -> String {
+        to_datetime(timestamp, self.offset(location), "%Y-%m-%d")
+    }
+
+    // Wind direction in degrees -> the selected --wind-dir-format
+    pub fn wind_dir(&self, deg: i32) -> Value {
+        let compass = |points: usize| {
+            let point = to_compass_points(deg as f32, points);
+            match &self.lang {
+                Some(lang) => Value::text(localize_compass(point, lang)),
+                None => Value::text(point),
+            }
+        };
+
+        match self.wind_dir {
+            WindDir::Deg => Value::number(deg, "°"),
+            WindDir::Compass8 => compass(8),
+            WindDir::Compass16 => compass(16),
+            WindDir::Compass32 => compass(32),
+            WindDir::Arrow => Value::text(to_arrow(deg as f32)),
+            WindDir::ArrowFrom => Value::text(to_arrow(deg as f32 + 180.0)),
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::data::convert::{
+    from_celsius, from_fahrenheight, from_mph, to_beaufort, to_celsius, to_fahrenheight, to_inches,
+    to_inhg, to_km, to_kmh, to_knots, to_miles, to_mmhg, to_mph,
+};
+
+// Unit system requested from OpenWeather. Temperatures and wind speeds come
+// back in the system's units, everything else is always metric
+#[derive(ValueEnum, Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystem {
+    #[value(aliases = ["K", "S"])]
+    #[serde(alias = "K", alias = "k", alias = "S", alias = "s")]
+    Standard,
+    #[default]
+    #[value(alias = "M")]
+    #[serde(alias = "M", alias = "m")]
+    Metric,
+    #[value(alias = "I")]
+    #[serde(alias = "I", alias = "i")]
+    Imperial,
+}
+
+#[derive(ValueEnum, Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TempUnit {
+    #[value(alias = "c")]
+    #[serde(alias = "c", alias = "C")]
+    Celsius,
+    #[value(alias = "f")]
+    #[serde(alias = "f", alias = "F")]
+    Fahrenheit,
+    #[value(alias = "k")]
+    #[serde(alias = "k", alias = "K")]
+    Kelvin,
+}
+
+#[derive(ValueEnum, Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WindUnit {
+    #[value(alias = "m/s")]
+    #[serde(alias = "m/s")]
+    Ms,
+    #[value(aliases = ["km/h", "kph"])]
+    #[serde(alias = "km/h", alias = "kph")]
+    Kmh,
+    Mph,
+    #[value(aliases = ["kn", "kt"])]
+    #[serde(alias = "kn", alias = "kt")]
+    Knots,
+    #[value(alias = "bft")]
+    #[serde(alias = "bft")]
+    Beaufort,
+}
+
+#[derive(ValueEnum, Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PressureUnit {
+    #[value(alias = "mbar")]
+    #[serde(alias = "hPa", alias = "mbar")]
+    Hpa,
+    #[serde(alias = "inHg")]
+    Inhg,
+    #[serde(alias = "mmHg")]
+    Mmhg,
+}
+
+#[derive(ValueEnum, Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DistanceUnit {
+    M,
+    Km,
+    #[value(alias = "miles")]
+    #[serde(alias = "miles")]
+    Mi,
+}
+
+#[derive(ValueEnum, Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PrecipUnit {
+    Mm,
+    #[value(alias = "inches")]
+    #[serde(alias = "inches")]
+    In,
+}
+
+impl UnitSystem {
+    // Value of the `units` query parameter
+    pub fn query(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Metric => "metric",
+            Self::Imperial => "imperial",
+        }
+    }
+
+    // Units the API reports temperatures and wind speeds in
+    fn temp(self) -> TempUnit {
+        match self {
+            Self::Standard => TempUnit::Kelvin,
+            Self::Metric => TempUnit::Celsius,
+            Self::Imperial => TempUnit::Fahrenheit,
+        }
+    }
+
+    fn wind(self) -> WindUnit {
+        match self {
+            Self::Imperial => WindUnit::Mph,
+            _ => WindUnit::Ms,
+        }
+    }
+}
+
+// Display unit of every quantity. Quantities without an override use the
+// units of the system
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Units {
+    pub system: UnitSystem,
+    pub temp: TempUnit,
+    pub wind: WindUnit,
+    pub pressure: PressureUnit,
+    pub distance: DistanceUnit,
+    pub precip: PrecipUnit,
+}
+
+impl From<UnitSystem> for Units {
+    fn from(system: UnitSystem) -> Self {
+        Self {
+            system,
+            temp: system.temp(),
+            wind: system.wind(),
+            pressure: PressureUnit::Hpa,
+            distance: DistanceUnit::M,
+            precip: match system {
+                UnitSystem::Imperial => PrecipUnit::In,
+                _ => PrecipUnit::Mm,
+            },
+        }
+    }
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        UnitSystem::default().into()
+    }
+}
+
+// Each of these takes a value as reported by the API and returns it as text
+// in the display unit, along with the unit's suffix
+impl Units {
+    // API temperature -> Celsius, for calculations
+    pub fn celsius(&self, temp: f32) -> f32 {
+        match self.system.temp() {
+            TempUnit::Celsius => temp,
+            TempUnit::Fahrenheit => to_celsius(from_fahrenheight(temp)),
+            TempUnit::Kelvin => to_celsius(temp),
+        }
+    }
+
+    // API wind speed -> m/s, for calculations
+    pub fn metres_per_second(&self, speed: f32) -> f32 {
+        match self.system.wind() {
+            WindUnit::Mph => from_mph(speed),
+            _ => speed,
+        }
+    }
+
+    pub fn temp(&self, temp: f32) -> (String, &'static str) {
+        let native = self.system.temp();
+        let kelvin = match native {
+            TempUnit::Celsius => from_celsius(temp),
+            TempUnit::Fahrenheit => from_fahrenheight(temp),
+            TempUnit::Kelvin => temp,
+        };
+
+        let temp = match self.temp {
+            unit if unit == native => temp,
+            TempUnit::Celsius => to_celsius(kelvin),
+            TempUnit::Fahrenheit => to_fahrenheight(kelvin),
+            TempUnit::Kelvin => kelvin,
+        };
+        (format!("{:.2}", temp), self.temp.suffix())
+    }
+
+    // A temperature in Celsius, such as a derived metric
+    pub fn temp_from_celsius(&self, temp: f32) -> (String, &'static str) {
+        let temp = match self.temp {
+            TempUnit::Celsius => temp,
+            TempUnit::Fahrenheit => to_fahrenheight(from_celsius(temp)),
+            TempUnit::Kelvin => from_celsius(temp),
+        };
+        (format!("{:.2}", temp), self.temp.suffix())
+    }
+
+    pub fn speed(&self, speed: f32) -> (String, &'static str) {
+        let native = self.system.wind();
+        let ms = self.metres_per_second(speed);
+
+        let speed = match self.wind {
+            unit if unit == native => format!("{:.2}", speed),
+            WindUnit::Ms => format!("{:.2}", ms),
+            WindUnit::Kmh => format!("{:.2}", to_kmh(ms)),
+            WindUnit::Mph => format!("{:.2}", to_mph(ms)),
+            WindUnit::Knots => format!("{:.2}", to_knots(ms)),
+            WindUnit::Beaufort => to_beaufort(ms).to_string(),
+        };
+        (speed, self.wind.suffix())
+    }
+
+    pub fn pressure(&self, pressure: f32) -> (String, &'static str) {
+        match self.pressure {
+            PressureUnit::Hpa => (format!("{:.0}", pressure), "hPa"),
+            PressureUnit::Inhg => (format!("{:.2}", to_inhg(pressure)), "inHg"),
+            PressureUnit::Mmhg => (format!("{:.0}", to_mmhg(pressure)), "mmHg"),
+        }
+    }
+
+    pub fn distance(&self, distance: f32) -> (String, &'static str) {
+        match self.distance {
+            DistanceUnit::M => (format!("{:.0}", distance), "m"),
+            DistanceUnit::Km => (format!("{:.1}", to_km(distance)), "km"),
+            DistanceUnit::Mi => (format!("{:.1}", to_miles(distance)), "mi"),
+        }
+    }
+
+    pub fn precipitation(&self, precipitation: f32) -> (String, &'static str) {
+        match self.precip {
+            PrecipUnit::Mm => (format!("{:.2}", precipitation), "mm"),
+            PrecipUnit::In => (format!("{:.2}", to_inches(precipitation)), "in"),
+        }
+    }
+}
+
+impl TempUnit {
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::Celsius => "°C",
+            Self::Fahrenheit => "°F",
+            Self::Kelvin => "°K",
+        }
+    }
+}
+
+impl WindUnit {
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::Ms => "m/s",
+            Self::Kmh => "km/h",
+            Self::Mph => "mph",
+            Self::Knots => "kn",
+            Self::Beaufort => "Bft",
+        }
+    }
+}
@@ -0,0 +1,209 @@
+mod common;
+
+use common::{mount, run, stderr, stdout, Owcli, KEY};
+use openweathercli::data::aqi::{
+    caqi, caqi_category, caqi_sub_index, epa_aqi, epa_category, epa_sub_index,
+};
+use wiremock::MockServer;
+
+#[test]
+fn epa_sub_indices_match_breakpoints() {
+    // (pollutant, μg/m³, AQI), with gases converted from the EPA's ppb and ppm
+    for (pollutant, concentration, expected) in [
+        ("pm2_5", 9.0, 50.0),
+        ("pm2_5", 12.0, 56.0),
+        ("pm2_5", 35.4, 100.0),
+        ("pm2_5", 55.5, 151.0),
+        ("pm10", 154.0, 100.0),
+        ("pm10", 425.0, 301.0),
+        ("o3", 137.5, 100.0),    // 70 ppb
+        ("o3", 589.0, 300.0),    // 300 ppb, between the 8 and 1 hour tables
+        ("o3", 795.3, 301.0),    // 405 ppb
+        ("o3", 883.5, 346.0),    // 450 ppb
+        ("no2", 101.7, 51.0),    // 54 ppb
+        ("so2", 196.6, 100.0),   // 75 ppb
+        ("co", 10_770.0, 100.0), // 9.4 ppm
+        ("pm2_5", 1000.0, 500.0),
+    ] {
+        assert_eq!(
+            epa_sub_index(pollutant, concentration),
+            Some(expected),
+            "{} at {}",
+            pollutant,
+            concentration
+        );
+    }
+    assert_eq!(epa_sub_index("nh3", 10.0), None);
+}
+
+#[test]
+fn caqi_sub_indices_match_grid() {
+    for (pollutant, concentration, expected) in [
+        ("no2", 75.0, 38.0),
+        ("pm10", 25.0, 25.0),
+        ("pm2_5", 55.0, 75.0),
+        ("o3", 240.0, 100.0),
+        ("co", 15_000.0, 88.0),
+        ("pm10", 270.0, 125.0),
+    ] {
+        assert_eq!(
+            caqi_sub_index(pollutant, concentration),
+            Some(expected),
+            "{} at {}",
+            pollutant,
+            concentration
+        );
+    }
+    assert_eq!(caqi_sub_index("nh3", 10.0), None);
+}
+
+#[test]
+fn overall_index_is_the_worst_pollutant() {
+    let concentrations = [
+        ("pm2_5", Some(40.0)),
+        ("o3", Some(110.0)),
+        ("nh3", Some(1.0)),
+        ("so2", None),
+    ];
+
+    assert_eq!(epa_aqi(&concentrations), Some(112.0));
+    assert_eq!(caqi(&concentrations), Some(60.0));
+    assert_eq!(epa_aqi(&[("nh3", Some(1.0))]), None);
+
+    assert_eq!(epa_category(112.0), "Unhealthy for Sensitive Groups");
+    assert_eq!(epa_category(301.0), "Hazardous");
+    assert_eq!(caqi_category(60.0), "Medium");
+    assert_eq!(caqi_category(101.0), "Very High");
+}
+
+async fn current(args: &[&str]) -> String {
+    let args = [&["--api", "air"][..], args].concat();
+    run("/data/2.5/air_pollution", "air_pollution.json", &args).await
+}
+
+#[tokio::test]
+async fn prints_current_air_quality() {
+    assert_eq!(
+        current(&[
+            "-v",
+            "-p",
+            "aqi,aqi_label,epa_aqi,epa_category,caqi,caqi_category,pm2_5"
+        ])
+        .await,
+        "Air Quality Index: 2\n\
+         Air Quality: Fair\n\
+         US EPA AQI: 56\n\
+         US EPA Category: Moderate\n\
+         European CAQI: 29\n\
+         CAQI Category: Low\n\
+         PM2.5: 12.00μg/m³\n"
+    );
+}
+
+#[tokio::test]
+async fn summarizes_current_air_quality() {
+    assert_eq!(
+        current(&["-s"]).await,
+        "Air quality: Fair (2), US EPA AQI 56 (Moderate), CAQI 29 (Low)\n  \
+         PM2.5: 12.00μg/m³\n  \
+         PM10: 20.50μg/m³\n  \
+         O3: 68.66μg/m³\n  \
+         NO2: 18.80μg/m³\n  \
+         SO2: 0.64μg/m³\n  \
+         CO: 201.94μg/m³\n  \
+         NO: 0.02μg/m³\n  \
+         NH3: 0.12μg/m³\n"
+    );
+    assert_eq!(
+        current(&["-s", "short"]).await,
+        "Air quality: Fair (2), US EPA AQI 56 (Moderate), CAQI 29 (Low)\n"
+    );
+}
+
+#[tokio::test]
+async fn renders_current_air_quality_as_an_object() {
+    assert_eq!(
+        current(&["-p", "aqi,epa_aqi", "--format", "json"]).await,
+        "{\n  \"aqi\": 2,\n  \"epa_aqi\": 56\n}\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_forecast_per_hour() {
+    let args = [
+        "--api",
+        "air-forecast",
+        "-p",
+        "aqi_label,epa_aqi,caqi",
+        "--format",
+        "csv",
+    ];
+
+    assert_eq!(
+        run(
+            "/data/2.5/air_pollution/forecast",
+            "air_pollution_forecast.json",
+            &args
+        )
+        .await,
+        "time,aqi_label,epa_aqi,caqi\n\
+         2019-06-12 07:00:00,Good,19,17\n\
+         2019-06-12 08:00:00,Poor,112,63\n"
+    );
+
+    let args = ["--api", "air-forecast", "-p", "epa_category"];
+    assert_eq!(
+        run(
+            "/data/2.5/air_pollution/forecast",
+            "air_pollution_forecast.json",
+            &args
+        )
+        .await,
+        "[2019-06-12 07:00:00] Good\n\
+         [2019-06-12 08:00:00] Unhealthy for Sensitive Groups\n"
+    );
+}
+
+#[tokio::test]
+async fn requests_history_between_start_and_end() {
+    let server = MockServer::start().await;
+    mount(
+        &server,
+        "/data/2.5/air_pollution/history",
+        200,
+        "air_pollution_forecast.json",
+    )
+    .await;
+
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k",
+            KEY,
+            "--lat",
+            "37.39",
+            "--lon",
+            "-122.08",
+            "--api",
+            "air-history",
+            "--start",
+            "2019-06-12 07:00",
+            "--end",
+            "2019-06-12 09:00",
+            "--tz",
+            "utc",
+            "-p",
+            "aqi",
+        ])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "[2019-06-12 15:00:00] 1\n[2019-06-12 16:00:00] 4\n"
+    );
+
+    let requests = server.received_requests().await.unwrap();
+    let query = requests[0].url.query().unwrap_or_default().to_string();
+    assert!(query.contains("start=1560322800"), "{}", query);
+    assert!(query.contains("end=1560330000"), "{}", query);
+}
@@ -0,0 +1,188 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::{mount, stderr, stdout, Owcli, KEY};
+use openweathercli::data::astro::{
+    crossing, moon_age, moon_illumination, moon_phase, solar_noon, solar_position,
+};
+use wiremock::MockServer;
+
+// 2019-06-12 in Mountain View, where OpenWeather reports sunrise at 1560343627
+// and sunset at 1560396563
+const LAT: f64 = 37.39;
+const LON: f64 = -122.08;
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2019, 6, 12).unwrap()
+}
+
+fn assert_within(actual: i64, expected: i64, seconds: i64) {
+    assert!(
+        (actual - expected).abs() <= seconds,
+        "expected {} ± {}s, got {}",
+        expected,
+        seconds,
+        actual
+    );
+}
+
+#[test]
+fn sunrise_and_sunset_match_the_api() {
+    let sunrise = crossing(date(), LAT, LON, -0.833, true).unwrap();
+    let sunset = crossing(date(), LAT, LON, -0.833, false).unwrap();
+
+    assert_within(sunrise, 1560343627, 60);
+    assert_within(sunset, 1560396563, 60);
+}
+
+#[test]
+fn twilight_matches_noaa() {
+    // NOAA solar calculator, 2019-06-12 at 37.39, -122.08 (UTC)
+    for (elevation, rising, expected) in [
+        (-6.0, true, 1560341760),   // 12:16
+        (-6.0, false, 1560398400),  // 04:00
+        (-12.0, true, 1560339420),  // 11:37
+        (-18.0, false, 1560403320), // 05:22
+    ] {
+        let time = crossing(date(), LAT, LON, elevation, rising).unwrap();
+        assert_within(time, expected, 120);
+    }
+}
+
+#[test]
+fn solar_noon_and_position_match_noaa() {
+    // Solar noon at 20:08 UTC, with the sun due south at 76° elevation
+    let noon = solar_noon(date(), LON);
+    assert_within(noon, 1560370080, 60);
+
+    let (elevation, azimuth) = solar_position(noon, LAT, LON);
+    assert!((elevation - 75.8).abs() < 0.2, "elevation {}", elevation);
+    assert!((azimuth - 180.0).abs() < 1.0, "azimuth {}", azimuth);
+}
+
+#[test]
+fn no_sunset_during_polar_day() {
+    let midsummer = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+
+    assert_eq!(crossing(midsummer, 78.2, 15.6, -0.833, true), None);
+    assert_eq!(crossing(midsummer, 78.2, 15.6, -0.833, false), None);
+}
+
+#[test]
+fn moon_phases_match_known_dates() {
+    // (UTC timestamp, phase)
+    for (timestamp, phase) in [
+        (947182440, "New Moon"),         // 2000-01-06 18:14
+        (1559556120, "New Moon"),        // 2019-06-03 10:02
+        (1560190500, "First Quarter"),   // 2019-06-10 18:55
+        (1560796680, "Full Moon"),       // 2019-06-17 08:31
+        (1561454040, "Last Quarter"),    // 2019-06-25 09:46
+        (1560340800, "Waxing Gibbous"),  // 2019-06-12 12:00
+        (1561800000, "Waning Crescent"), // 2019-06-29 09:20
+    ] {
+        assert_eq!(moon_phase(timestamp), phase, "at {}", timestamp);
+    }
+
+    assert!(moon_age(947182440) < 0.01);
+    assert!(moon_illumination(947182440) < 0.01);
+    assert!(moon_illumination(1560796680) > 0.99);
+}
+
+async fn run(args: &[&str]) -> std::process::Output {
+    let server = MockServer::start().await;
+    mount(&server, "/data/2.5/weather", 200, "current_weather.json").await;
+
+    let base = ["--lat", "37.39", "--lon", "-122.08"];
+    Owcli::new(&server).run(&[&base[..], args].concat()).await
+}
+
+#[tokio::test]
+async fn prints_astro_options_without_a_key() {
+    let output = run(&[
+        "--api",
+        "astro",
+        "--date",
+        "2019-06-12",
+        "--tz",
+        "utc",
+        "-v",
+        "-p",
+        "sunrise,sunset,twilight,golden_hour,moon_phase",
+    ])
+    .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "Sunrise: 12:47\n\
+         Sunset: 03:29\n\
+         Civil Twilight: 12:16-12:47, 03:29-04:00\n\
+         Golden Hour: 12:47-13:25, 02:50-03:29\n\
+         Moon Phase: Waxing Gibbous\n"
+    );
+}
+
+#[tokio::test]
+async fn day_length_covers_polar_day_and_night() {
+    let server = MockServer::start().await;
+
+    // Longyearbyen, where the sun neither sets in June nor rises in December
+    for (date, expected) in [("2019-06-21", "24h 00m\n"), ("2019-12-21", "0h 00m\n")] {
+        let output = Owcli::new(&server)
+            .run(&[
+                "--lat",
+                "78.22",
+                "--lon",
+                "15.65",
+                "--api",
+                "astro",
+                "--date",
+                date,
+                "-p",
+                "day_length",
+            ])
+            .await;
+
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert_eq!(stdout(&output), expected);
+    }
+}
+
+#[tokio::test]
+async fn date_takes_a_time_of_day() {
+    let output = run(&[
+        "--api",
+        "astro",
+        "--date",
+        "2019-06-12 20:08",
+        "--tz",
+        "utc",
+        "-p",
+        "solar_azimuth,solar_elevation",
+    ])
+    .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "179.81°\n75.78°\n");
+}
+
+#[tokio::test]
+async fn rejects_invalid_dates() {
+    let output = run(&["--api", "astro", "--date", "2019-13-01"]).await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Invalid date: 2019-13-01"));
+}
+
+#[tokio::test]
+async fn prints_astro_options_for_current_weather() {
+    let output = run(&["-k", KEY, "-p", "twilight,blue_hour,moon_phase"]).await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "05:16-05:47, 20:29-21:00\n\
+         05:16-05:28, 20:48-21:00\n\
+         Waxing Gibbous\n"
+    );
+}
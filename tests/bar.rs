@@ -0,0 +1,88 @@
+mod common;
+
+use common::{mount, stdout, Owcli, KEY};
+use wiremock::MockServer;
+
+async fn run(owcli: fn(&MockServer) -> Owcli, args: &[&str]) -> String {
+    let server = MockServer::start().await;
+    mount(&server, "/data/2.5/weather", 200, "current_weather.json").await;
+
+    let base = ["-k", KEY, "--lat", "37.39", "--lon", "-122.08"];
+    let output = owcli(&server).run(&[&base[..], args].concat()).await;
+
+    assert!(output.status.success());
+    stdout(&output)
+}
+
+#[tokio::test]
+async fn prints_waybar_json() {
+    let output = run(Owcli::new, &["--output", "waybar"]).await;
+    let block: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(
+        block,
+        serde_json::json!({
+            "text": "9°C light rain",
+            "tooltip": "Mountain View, US\nlight rain\nFeels like 9°C\nHumidity 100%\nWind 5m/s N",
+            "class": ["rain", "cold"],
+            "percentage": 100
+        })
+    );
+}
+
+#[tokio::test]
+async fn prints_i3blocks_lines() {
+    assert_eq!(
+        run(Owcli::new, &["--output", "i3blocks"]).await,
+        "9°C light rain\n9°C\n#81a1c1\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_polybar_colors() {
+    assert_eq!(
+        run(Owcli::new, &["--output", "polybar"]).await,
+        "%{F#81a1c1}9°C light rain%{F-}\n"
+    );
+}
+
+#[tokio::test]
+async fn picks_default_bands_in_display_units() {
+    assert_eq!(
+        run(Owcli::new, &["--units", "I", "--output", "i3blocks"]).await,
+        "49°F light rain\n49°F\n#81a1c1\n"
+    );
+}
+
+#[tokio::test]
+async fn uses_bands_and_colors_from_config() {
+    let output = run(
+        |server| {
+            Owcli::new(server).with_config(
+                "bar:\n  \
+                   text: \"{temp:.1}\"\n  \
+                   percentage: clouds\n  \
+                   bands:\n    \
+                     - { below: 5, class: chilly }\n    \
+                     - { class: pleasant, color: \"#00ff00\" }\n  \
+                   colors:\n    \
+                     rain: \"#0000ff\"\n",
+            )
+        },
+        &["--output", "waybar"],
+    )
+    .await;
+    let block: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(block["text"], "9.4");
+    assert_eq!(block["class"], serde_json::json!(["rain", "pleasant"]));
+    assert_eq!(block["percentage"], 90);
+
+    let output = run(
+        |server| Owcli::new(server).with_config("bar:\n  colors:\n    rain: \"#0000ff\"\n"),
+        &["--output", "polybar"],
+    )
+    .await;
+
+    assert_eq!(output, "%{F#0000ff}9°C light rain%{F-}\n");
+}
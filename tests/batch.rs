@@ -0,0 +1,215 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{fixture, mount_weather_at, stderr, stdout, Owcli, KEY};
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const CONFIG: &str = "\
+key: test-key
+locations:
+  home:
+    lat: 37.39
+    lon: -122.08
+  berlin:
+    lat: 52.52
+    lon: 13.405
+    units: imperial
+  nowhere:
+    lat: 1.5
+    lon: 1.5
+";
+
+// Home, Berlin and geocoded Mountain View are served, any other place is not
+// found
+async fn server() -> MockServer {
+    let server = MockServer::start().await;
+    mount_weather_at(&server, "37.39", "-122.08", Duration::ZERO).await;
+    mount_weather_at(&server, "52.52", "13.405", Duration::ZERO).await;
+    mount_weather_at(&server, "37.3893889", "-122.0832101", Duration::ZERO).await;
+
+    Mock::given(method("GET"))
+        .and(path("/data/2.5/weather"))
+        .respond_with(ResponseTemplate::new(404).set_body_string(fixture("error_404.json")))
+        .with_priority(10)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/geo/1.0/direct"))
+        .and(query_param("q", "Mountain View,CA,US"))
+        .and(query_param("appid", KEY))
+        .respond_with(ResponseTemplate::new(200).set_body_string(fixture("geocoding_direct.json")))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+fn locations_file(dir: &tempfile::TempDir, csv: &str) -> String {
+    let path = dir.path().join("places.csv");
+    std::fs::write(&path, csv).expect("Could not write locations file");
+    path.display().to_string()
+}
+
+#[tokio::test]
+async fn compares_saved_locations_in_a_table() {
+    let server = server().await;
+    let output = Owcli::new(&server)
+        .with_config(CONFIG)
+        .run(&["--location", "home,berlin", "-p", "temp,humidity"])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "location  temp     humidity\n\
+         home      9.40°C   100%\n\
+         berlin    48.92°F  100%\n"
+    );
+}
+
+#[tokio::test]
+async fn accepts_repeated_locations() {
+    let server = server().await;
+    let owcli = Owcli::new(&server).with_config(CONFIG);
+
+    let repeated = owcli
+        .run(&["--location", "berlin", "--location", "home", "-p", "temp"])
+        .await;
+    let listed = owcli
+        .run(&["--location", "berlin,home", "-p", "temp"])
+        .await;
+
+    assert!(repeated.status.success(), "{}", stderr(&repeated));
+    assert_eq!(stdout(&repeated), stdout(&listed));
+    assert!(stdout(&repeated).starts_with("location  temp\nberlin"));
+}
+
+#[tokio::test]
+async fn reads_locations_from_a_file() {
+    let server = server().await;
+    let dir = tempfile::tempdir().unwrap();
+    let file = locations_file(
+        &dir,
+        "name,lat,lon,city,state,country,zip,units\n\
+         # Places to compare\n\
+         office,52.52,13.405,,,,,imperial\n\
+         ,,,Mountain View,CA,US,,\n",
+    );
+
+    let output = Owcli::new(&server)
+        .with_config(CONFIG)
+        .run(&["--locations-file", &file, "-p", "temp", "--format", "csv"])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "location,temp,error\noffice,48.92,\n\"Mountain View, CA, US\",9.40,\n"
+    );
+}
+
+#[tokio::test]
+async fn reports_failed_locations_without_stopping() {
+    let server = server().await;
+    let output = Owcli::new(&server)
+        .with_config(CONFIG)
+        .run(&["--location", "home,nowhere,berlin", "-p", "temp"])
+        .await;
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "location  temp     error\n\
+         home      9.40°C\n\
+         nowhere            Not found: city not found\n\
+         berlin    48.92°F\n"
+    );
+    assert!(stderr(&output).contains("1 of 3 locations failed"));
+}
+
+#[tokio::test]
+async fn renders_a_json_array() {
+    let server = server().await;
+    let output = Owcli::new(&server)
+        .with_config(CONFIG)
+        .run(&[
+            "--location",
+            "home,nowhere",
+            "-p",
+            "temp",
+            "--format",
+            "json",
+        ])
+        .await;
+
+    assert_eq!(output.status.code(), Some(1));
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([
+            { "location": "home", "temp": 9.4, "error": null },
+            { "location": "nowhere", "temp": null, "error": "Not found: city not found" },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn limits_concurrent_requests() {
+    let server = MockServer::start().await;
+    let delay = Duration::from_millis(200);
+    for lat in ["1.5", "2.5", "3.5"] {
+        mount_weather_at(&server, lat, "1.5", delay).await;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let file = locations_file(&dir, "name,lat,lon\na,1.5,1.5\nb,2.5,1.5\nc,3.5,1.5\n");
+
+    let start = Instant::now();
+    let output = Owcli::new(&server)
+        .with_config(CONFIG)
+        .run(&[
+            "--locations-file",
+            &file,
+            "-p",
+            "temp",
+            "--concurrency",
+            "1",
+        ])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(start.elapsed() >= delay * 3);
+    assert_eq!(stdout(&output).lines().count(), 4);
+}
+
+#[tokio::test]
+async fn rejects_invalid_locations_files() {
+    let server = server().await;
+    let dir = tempfile::tempdir().unwrap();
+    let file = locations_file(&dir, "name,lat\nhalfway,52.52\n");
+
+    let output = Owcli::new(&server)
+        .with_config(CONFIG)
+        .run(&["--locations-file", &file, "-p", "temp"])
+        .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("halfway needs lat and lon, a city, or a zip and country"));
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn only_compares_current_weather() {
+    let server = server().await;
+    let output = Owcli::new(&server)
+        .with_config(CONFIG)
+        .run(&["--location", "home,berlin", "--api", "forecast"])
+        .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--api forecast does not support several locations"));
+}
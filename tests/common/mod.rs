@@ -0,0 +1,218 @@
+#![allow(dead_code)]
+
+use std::{
+    path::PathBuf,
+    process::{Output, Stdio},
+    time::Duration,
+};
+
+use tokio::io::AsyncWriteExt;
+
+use tempfile::TempDir;
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+pub const KEY: &str = "test-key";
+
+pub fn fixture(name: &str) -> String {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "fixtures", name]
+        .iter()
+        .collect();
+
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Could not read fixture {}: {}", path.display(), e))
+}
+
+fn respond(status: u16, name: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status)
+        .insert_header("content-type", "application/json")
+        .set_body_string(fixture(name))
+}
+
+// Serves a recorded fixture with the given status for GET requests to `route`
+// that carry the test API key. Like the real API, requests for imperial or
+// standard units get `<name>_imperial.json` or `<name>_standard.json` when
+// those exist, and the metric fixture otherwise
+pub async fn mount(server: &MockServer, route: &str, status: u16, name: &str) {
+    for units in ["imperial", "standard"] {
+        let variant = name.replace(".json", &format!("_{}.json", units));
+        let exists = [env!("CARGO_MANIFEST_DIR"), "tests", "fixtures", &variant]
+            .iter()
+            .collect::<PathBuf>()
+            .exists();
+
+        if exists {
+            Mock::given(method("GET"))
+                .and(path(route))
+                .and(query_param("appid", KEY))
+                .and(query_param("units", units))
+                .respond_with(respond(status, &variant))
+                .with_priority(1)
+                .mount(server)
+                .await;
+        }
+    }
+
+    Mock::given(method("GET"))
+        .and(path(route))
+        .and(query_param("appid", KEY))
+        .respond_with(respond(status, name))
+        .mount(server)
+        .await;
+}
+
+// A server with a single fixture mounted, see `mount`
+pub async fn serve(route: &str, name: &str) -> MockServer {
+    let server = MockServer::start().await;
+    mount(&server, route, 200, name).await;
+    server
+}
+
+// Serves the current weather only for requests at the given coordinates,
+// after `delay`, in imperial units when asked for and metric otherwise
+pub async fn mount_weather_at(server: &MockServer, lat: &str, lon: &str, delay: Duration) {
+    for (units, name, priority) in [
+        (Some("imperial"), "current_weather_imperial.json", 1),
+        (None, "current_weather.json", 5),
+    ] {
+        let mut mock = Mock::given(method("GET"))
+            .and(path("/data/2.5/weather"))
+            .and(query_param("lat", lat))
+            .and(query_param("lon", lon))
+            .and(query_param("appid", KEY));
+        if let Some(units) = units {
+            mock = mock.and(query_param("units", units));
+        }
+
+        mock.respond_with(respond(200, name).set_delay(delay))
+            .with_priority(priority)
+            .mount(server)
+            .await;
+    }
+}
+
+pub struct Owcli {
+    // Keeps the isolated config directory alive for the duration of the test
+    home: TempDir,
+    base_url: String,
+    env: Vec<(String, String)>,
+}
+
+impl Owcli {
+    pub fn new(server: &MockServer) -> Self {
+        Self {
+            home: tempfile::tempdir().expect("Could not create temporary directory"),
+            base_url: server.uri(),
+            env: vec![],
+        }
+    }
+
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_config(self, yaml: &str) -> Self {
+        let dir = self.home.path().join("owcli");
+        std::fs::create_dir_all(&dir).expect("Could not create config directory");
+        std::fs::write(dir.join("config.yaml"), yaml).expect("Could not write config file");
+        self
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.home.path().join("owcli").join("config.yaml")
+    }
+
+    // Directory the binary runs in, below the isolated home directory
+    pub fn project_dir(&self) -> PathBuf {
+        let dir = self.home.path().join("project");
+        std::fs::create_dir_all(&dir).expect("Could not create project directory");
+        dir
+    }
+
+    // Writes `name` into the isolated home directory, returning its path
+    pub fn write(&self, name: &str, contents: &str) -> PathBuf {
+        let path = self.home.path().join(name);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).expect("Could not create directory");
+        }
+        std::fs::write(&path, contents).expect("Could not write file");
+        path
+    }
+
+    fn command(&self, args: &[&str]) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(env!("CARGO_BIN_EXE_openweathercli"));
+        command
+            .args(args)
+            .current_dir(self.project_dir())
+            .env_clear()
+            .env("HOME", self.home.path())
+            .env("XDG_CONFIG_HOME", self.home.path())
+            .env("OWCLI_BASE_URL", &self.base_url)
+            .envs(self.env.iter().cloned());
+        command
+    }
+
+    pub async fn run(&self, args: &[&str]) -> Output {
+        self.command(args)
+            .output()
+            .await
+            .expect("Could not run owcli")
+    }
+
+    // Runs with `input` on stdin, e.g. the answers to a prompt
+    pub async fn run_with_input(&self, args: &[&str], input: &str) -> Output {
+        let mut child = self
+            .command(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Could not run owcli");
+
+        let mut stdin = child.stdin.take().expect("No stdin");
+        stdin
+            .write_all(input.as_bytes())
+            .await
+            .expect("Could not write to stdin");
+        drop(stdin);
+
+        child.wait_with_output().await.expect("Could not run owcli")
+    }
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+// Runs owcli with the test key at Mountain View, against a server with
+// `name` mounted at `route` and with `config` as the configuration file
+// unless it is empty
+pub async fn output(route: &str, name: &str, config: &str, args: &[&str]) -> Output {
+    let server = serve(route, name).await;
+    let mut owcli = Owcli::new(&server);
+    if !config.is_empty() {
+        owcli = owcli.with_config(config);
+    }
+
+    let base = ["-k", KEY, "--lat", "37.39", "--lon", "-122.08"];
+    owcli.run(&[&base[..], args].concat()).await
+}
+
+// The stdout of a successful `output`
+pub async fn run_with_config(route: &str, name: &str, config: &str, args: &[&str]) -> String {
+    let output = output(route, name, config, args).await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    stdout(&output)
+}
+
+pub async fn run(route: &str, name: &str, args: &[&str]) -> String {
+    run_with_config(route, name, "", args).await
+}
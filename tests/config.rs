@@ -0,0 +1,197 @@
+mod common;
+
+use common::{serve, stderr, stdout, Owcli};
+
+const USER: &str = "\
+key: test-key
+lat: 37.39
+lon: -122.08
+units: imperial
+templates:
+  short: \"{temp}\"
+";
+
+#[tokio::test]
+async fn is_silent_without_configuration_files() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k", "test-key", "--lat", "37.39", "--lon", "-122.08", "-p", "temp",
+        ])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "9.40°C\n");
+    assert_eq!(stderr(&output), "");
+}
+
+#[tokio::test]
+async fn project_file_overrides_the_user_file() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(USER);
+    owcli.write("project/.owcli.yaml", "units: metric\n");
+
+    let output = owcli.run(&["-p", "temp"]).await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "9.40°C\n");
+}
+
+#[tokio::test]
+async fn finds_the_project_file_in_a_parent_directory() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(USER);
+    owcli.write(".owcli.yaml", "units: metric\n");
+
+    let output = owcli.run(&["-p", "temp"]).await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "9.40°C\n");
+}
+
+#[tokio::test]
+async fn merges_sections_entry_by_entry() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(USER);
+    owcli.write(
+        "project/.owcli.yaml",
+        "templates:\n  long: \"{temp} {description}\"\n",
+    );
+
+    let short = owcli.run(&["-t", "short"]).await;
+    let long = owcli.run(&["-t", "long"]).await;
+
+    assert_eq!(stdout(&short), "48.92\n");
+    assert_eq!(stdout(&long), "48.92 light rain\n");
+}
+
+#[tokio::test]
+async fn explicit_file_takes_precedence() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(USER);
+    owcli.write("project/.owcli.yaml", "units: metric\n");
+    let explicit = owcli.write("explicit.yaml", "units: standard\n");
+
+    let flag = owcli
+        .run(&["--config", explicit.to_str().unwrap(), "-p", "temp"])
+        .await;
+    assert!(flag.status.success(), "{}", stderr(&flag));
+    assert_eq!(stdout(&flag), "282.55°K\n");
+
+    let owcli = owcli.with_env("OWCLI_CONFIG", explicit.to_str().unwrap());
+    let env = owcli.run(&["-p", "temp"]).await;
+    assert_eq!(stdout(&env), "282.55°K\n");
+}
+
+#[tokio::test]
+async fn flag_takes_precedence_over_the_environment() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(USER);
+    let metric = owcli.write("metric.yaml", "units: metric\n");
+    let standard = owcli.write("standard.yaml", "units: standard\n");
+
+    let output = owcli
+        .with_env("OWCLI_CONFIG", standard.to_str().unwrap())
+        .run(&["--config", metric.to_str().unwrap(), "-p", "temp"])
+        .await;
+
+    assert_eq!(stdout(&output), "9.40°C\n");
+}
+
+#[tokio::test]
+async fn rejects_a_missing_explicit_file() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .with_config(USER)
+        .run(&["--config", "missing.yaml", "-p", "temp"])
+        .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Could not read the configuration file missing.yaml"));
+}
+
+#[tokio::test]
+async fn skips_invalid_discovered_files() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(USER);
+    owcli.write("project/.owcli.yaml", "units: [metric\n");
+
+    let output = owcli.run(&["-p", "temp"]).await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "48.92°F\n");
+    assert!(stderr(&output).contains("Unable to read the configuration file"));
+    assert!(stderr(&output).contains(".owcli.yaml"));
+}
+
+#[tokio::test]
+async fn edits_the_explicit_file() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(USER);
+    let explicit = owcli.write("explicit.yaml", "");
+
+    let output = owcli
+        .run(&[
+            "--config",
+            explicit.to_str().unwrap(),
+            "location",
+            "add",
+            "home",
+            "--city",
+            "Berlin",
+        ])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        std::fs::read_to_string(&explicit).unwrap(),
+        "locations:\n  home:\n    city: Berlin\n"
+    );
+    assert_eq!(std::fs::read_to_string(owcli.config_path()).unwrap(), USER);
+}
+
+#[tokio::test]
+async fn merges_a_unit_system_with_a_units_section() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(USER);
+    owcli.write("project/.owcli.yaml", "units:\n  wind: knots\n");
+
+    let output = owcli.run(&["config", "show", "--origin"]).await;
+    let project = owcli.project_dir().join(".owcli.yaml");
+    assert!(stdout(&output).contains(&format!(
+        "units: imperial  # {}\n",
+        owcli.config_path().display()
+    )));
+    assert!(stdout(&output).contains(&format!("wind_unit: knots  # {}\n", project.display())));
+
+    let output = owcli.run(&["-p", "temp"]).await;
+    assert_eq!(stdout(&output), "48.92°F\n");
+
+    // And the other way around
+    owcli.write("project/.owcli.yaml", "units: metric\n");
+    std::fs::write(
+        owcli.config_path(),
+        USER.replace("units: imperial", "units:\n  wind: knots"),
+    )
+    .unwrap();
+    let output = owcli.run(&["config", "show"]).await;
+    assert!(
+        stdout(&output).contains("units: metric\nwind_unit: knots\n"),
+        "{}",
+        stdout(&output)
+    );
+}
+
+#[tokio::test]
+async fn sets_a_unit_over_a_unit_system() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config("units: imperial\n");
+
+    let output = owcli.run(&["config", "set", "units.wind", "knots"]).await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        std::fs::read_to_string(owcli.config_path()).unwrap(),
+        "units:\n  system: imperial\n  wind: knots\n"
+    );
+}
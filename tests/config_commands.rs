@@ -0,0 +1,327 @@
+mod common;
+
+use common::{fixture, mount, stderr, stdout, Owcli};
+use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+const USER: &str = "\
+key: test-key
+units: imperial
+locations:
+  home:
+    lat: 37.39
+    lon: -122.08
+";
+
+async fn validate(owcli: &Owcli, project: &str) -> std::process::Output {
+    owcli.write("project/.owcli.yaml", project);
+    owcli.run(&["config", "validate"]).await
+}
+
+#[tokio::test]
+async fn validates_each_file() {
+    let server = MockServer::start().await;
+    let owcli = Owcli::new(&server).with_config(USER);
+
+    let output = validate(&owcli, "default_location: home\n").await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "{}: ok\n{}: ok\n",
+            owcli.config_path().display(),
+            owcli.project_dir().join(".owcli.yaml").display()
+        )
+    );
+}
+
+#[tokio::test]
+async fn rejects_unknown_keys() {
+    let server = MockServer::start().await;
+    let owcli = Owcli::new(&server).with_config(USER);
+
+    let output = validate(&owcli, "latitude: 52.52\n").await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout(&output).contains("unknown field `latitude`"));
+    assert!(stderr(&output).contains("Invalid configuration: 1 of 2 files have errors"));
+}
+
+#[tokio::test]
+async fn rejects_bad_values() {
+    let server = MockServer::start().await;
+    let owcli = Owcli::new(&server);
+
+    for (yaml, error) in [
+        ("lat: 123\n", "`lat` must be between -90 and 90, not 123"),
+        ("lon: east\n", "`lon` must be a number, not east"),
+        (
+            "locations:\n  away:\n    lat: 1\n    lon: 200\n",
+            "away: `lon` must be between -180 and 180, not 200",
+        ),
+        (
+            "units: metrik\n",
+            "`units` to be metric, imperial, standard",
+        ),
+        ("units:\n  wind: knotz\n", "a section of valid units"),
+        ("time_format: \"%Q\"\n", "Invalid time format: %Q"),
+        (
+            "templates:\n  broken: \"{}\"\n",
+            "template broken: Invalid template",
+        ),
+        (
+            "default_location: office\n",
+            "default_location office is not in `locations`",
+        ),
+    ] {
+        let output = validate(&owcli, yaml).await;
+
+        assert_eq!(output.status.code(), Some(2), "{}", yaml);
+        assert!(
+            stdout(&output).contains(error),
+            "{}: {}",
+            yaml,
+            stdout(&output)
+        );
+    }
+}
+
+#[tokio::test]
+async fn accepts_the_example_configuration() {
+    let server = MockServer::start().await;
+    let example = concat!(env!("CARGO_MANIFEST_DIR"), "/config.yaml");
+
+    let output = Owcli::new(&server)
+        .run(&["--config", example, "config", "validate"])
+        .await;
+
+    assert!(output.status.success(), "{}", stdout(&output));
+}
+
+#[tokio::test]
+async fn sets_values() {
+    let server = MockServer::start().await;
+    let owcli = Owcli::new(&server).with_config("key: test-key\n");
+
+    for (key, value) in [
+        ("units.wind", "knots"),
+        ("lat", "52.50"),
+        ("zip", "01067"),
+        ("locations.away.city", "Berlin"),
+    ] {
+        let output = owcli.run(&["config", "set", key, value]).await;
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert_eq!(
+            stdout(&output),
+            format!("Set {} in {}\n", key, owcli.config_path().display())
+        );
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(owcli.config_path()).unwrap(),
+        "key: test-key\n\
+         units:\n  wind: knots\n\
+         lat: 52.5\n\
+         zip: '01067'\n\
+         locations:\n  away:\n    city: Berlin\n"
+    );
+}
+
+#[tokio::test]
+async fn does_not_set_invalid_values() {
+    let server = MockServer::start().await;
+    let owcli = Owcli::new(&server).with_config(USER);
+
+    for (key, value) in [("latitude", "52.5"), ("units", "metrik"), ("key.part", "x")] {
+        let output = owcli.run(&["config", "set", key, value]).await;
+        assert_eq!(output.status.code(), Some(2), "{}", key);
+    }
+
+    assert_eq!(std::fs::read_to_string(owcli.config_path()).unwrap(), USER);
+}
+
+#[tokio::test]
+async fn shows_the_merged_configuration() {
+    let server = MockServer::start().await;
+    let owcli = Owcli::new(&server).with_config(USER);
+    owcli.write("project/.owcli.yaml", "templates:\n  short: \"{temp}\"\n");
+
+    let output = owcli.run(&["config", "show"]).await;
+
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "key: ********\n\
+             units: imperial\n\
+             tz: location\n\
+             icons: emoji\n\
+             wind_dir_format: deg\n\
+             base_url: {}\n\
+             locations:\n  home:\n    lat: 37.39\n    lon: -122.08\n\
+             templates:\n  short: '{{temp}}'\n",
+            server.uri()
+        )
+    );
+}
+
+// Accepts only the test key, like the real API
+async fn api() -> MockServer {
+    let server = MockServer::start().await;
+    mount(&server, "/data/2.5/weather", 200, "current_weather.json").await;
+    mount(
+        &server,
+        "/geo/1.0/direct",
+        200,
+        "geocoding_direct_springfield.json",
+    )
+    .await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(401).set_body_string(fixture("error_401.json")))
+        .with_priority(10)
+        .mount(&server)
+        .await;
+
+    server
+}
+
+#[tokio::test]
+async fn sets_up_a_configuration_file() {
+    let server = api().await;
+    let owcli = Owcli::new(&server);
+
+    let output = owcli
+        .run_with_input(
+            &["config", "init"],
+            "wrong-key\ntest-key\n\nSpringfield,US\n9\n2\nI\n",
+        )
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("The API key was rejected: Invalid API key."));
+    assert!(stderr(&output).contains("  2. Springfield, Missouri, US (37.2082, -93.2923)"));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "Saved the configuration to {}\n",
+            owcli.config_path().display()
+        )
+    );
+    let key_file = owcli.config_path().with_file_name("key");
+    assert_eq!(
+        std::fs::read_to_string(owcli.config_path()).unwrap(),
+        format!(
+            "units: imperial\n\
+             key_file: {}\n\
+             locations:\n  home:\n    \
+               lat: 37.2081729\n    \
+               lon: -93.2922715\n    \
+               name: Springfield\n    \
+               state: Missouri\n    \
+               country: US\n\
+             default_location: home\n",
+            key_file.display()
+        )
+    );
+    assert_eq!(std::fs::read_to_string(&key_file).unwrap(), "test-key\n");
+
+    #[cfg(unix)]
+    for path in [owcli.config_path(), key_file] {
+        assert_eq!(mode(&path), 0o600, "{}", path.display());
+    }
+
+    // The new file works as is
+    let output = owcli.run(&["-p", "temp"]).await;
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[cfg(unix)]
+fn mode(path: &std::path::Path) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+#[tokio::test]
+async fn warns_about_a_key_in_the_configuration_file() {
+    let server = api().await;
+    let owcli = Owcli::new(&server).with_config("units: imperial\n");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(0o644);
+        std::fs::set_permissions(owcli.config_path(), permissions).unwrap();
+    }
+    let plain = if cfg!(feature = "keyring") { "3" } else { "2" };
+
+    let output = owcli
+        .run_with_input(&["config", "init"], &format!("test-key\n{}\n\n\n", plain))
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("The key is stored in plain text in"));
+    assert_eq!(
+        std::fs::read_to_string(owcli.config_path()).unwrap(),
+        "units: imperial\nkey: test-key\n"
+    );
+    #[cfg(unix)]
+    assert_eq!(mode(&owcli.config_path()), 0o600);
+}
+
+#[tokio::test]
+async fn keeps_the_current_key_and_units() {
+    let server = api().await;
+    let owcli = Owcli::new(&server).with_config("key: test-key\nunits:\n  wind: knots\n");
+
+    let output = owcli.run_with_input(&["config", "init"], "\n\n\n").await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        std::fs::read_to_string(owcli.config_path()).unwrap(),
+        "key: test-key\nunits:\n  wind: knots\n  system: metric\n"
+    );
+}
+
+#[tokio::test]
+async fn stops_at_the_end_of_input() {
+    let server = api().await;
+    let owcli = Owcli::new(&server);
+
+    let output = owcli.run_with_input(&["config", "init"], "").await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Aborted"));
+    assert!(!owcli.config_path().exists());
+}
+
+#[tokio::test]
+async fn moves_a_plain_key_out_of_the_configuration_file() {
+    let server = api().await;
+    let owcli = Owcli::new(&server).with_config("key: old-key\nunits: metric\n");
+
+    let output = owcli
+        .run_with_input(&["config", "init"], "test-key\n\n\n\n")
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = std::fs::read_to_string(owcli.config_path()).unwrap();
+    assert!(!text.contains("key: "), "{}", text);
+    assert!(text.contains("key_file: "));
+
+    let output = owcli
+        .run(&["--lat", "1.5", "--lon", "1.5", "-p", "temp"])
+        .await;
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[tokio::test]
+async fn describes_the_subcommands_in_the_help() {
+    let server = MockServer::start().await;
+    let owcli = Owcli::new(&server);
+
+    let config = owcli.run(&["config", "--help"]).await;
+    let add = owcli.run(&["location", "add", "--help"]).await;
+
+    assert!(config.status.success(), "{}", stderr(&config));
+    assert!(stdout(&config).contains("init      Set up a configuration file interactively"));
+    assert!(stdout(&add).contains("Language used for this location only"));
+}
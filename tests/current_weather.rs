@@ -0,0 +1,198 @@
+mod common;
+
+use common::{mount, serve, stderr, stdout, Owcli, KEY};
+use wiremock::MockServer;
+
+const ALL: &str = "lat,lon,id,weather,description,icon,temp,feels_like,temp_min,temp_max,pressure,humidity,visibility,wind_speed,wind_dir,wind_gust,rain_1h,clouds";
+
+#[tokio::test]
+async fn prints_metric_values() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k", KEY, "--lat", "37.39", "--lon", "-122.08", "--units", "M", "-p", ALL,
+        ])
+        .await;
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "37.3861\n\
+         -122.0839\n\
+         500\n\
+         Rain\n\
+         light rain\n\
+         10d\n\
+         9.40°C\n\
+         8.71°C\n\
+         7.22°C\n\
+         11.11°C\n\
+         1023hPa\n\
+         100%\n\
+         10000m\n\
+         4.60m/s\n\
+         350°\n\
+         7.20m/s\n\
+         2.54mm\n\
+         90%\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_imperial_values() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k", KEY, "--lat", "37.39", "--lon", "-122.08", "--units", "I", "-p", ALL,
+        ])
+        .await;
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "37.3861\n\
+         -122.0839\n\
+         500\n\
+         Rain\n\
+         light rain\n\
+         10d\n\
+         48.92°F\n\
+         47.68°F\n\
+         45.00°F\n\
+         52.00°F\n\
+         1023hPa\n\
+         100%\n\
+         10000m\n\
+         10.29mph\n\
+         350°\n\
+         16.11mph\n\
+         0.10in\n\
+         90%\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_standard_values() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k",
+            KEY,
+            "--lat",
+            "37.39",
+            "--lon",
+            "-122.08",
+            "--units",
+            "S",
+            "-p",
+            "temp,feels_like,wind_speed,rain_1h",
+        ])
+        .await;
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "282.55°K\n281.86°K\n4.60m/s\n2.54mm\n");
+}
+
+#[tokio::test]
+async fn prints_verbose_labels() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k",
+            KEY,
+            "--lat",
+            "37.39",
+            "--lon",
+            "-122.08",
+            "-v",
+            "-p",
+            "temp,humidity,wind_dir",
+        ])
+        .await;
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "Current Temperature: 9.40°C\nHumidity: 100%\nWind Direction: 350°\n"
+    );
+}
+
+#[tokio::test]
+async fn reads_units_and_key_from_config() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .with_config(&format!(
+            "key: {}\nunits: I\nlat: \"37.39\"\nlon: \"-122.08\"\n",
+            KEY
+        ))
+        .run(&["-p", "temp"])
+        .await;
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "48.92°F\n");
+}
+
+#[tokio::test]
+async fn tolerates_missing_optional_fields() {
+    let server = MockServer::start().await;
+    mount(
+        &server,
+        "/data/2.5/weather",
+        200,
+        "current_weather_minimal.json",
+    )
+    .await;
+
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k",
+            KEY,
+            "--lat",
+            "37.39",
+            "--lon",
+            "-122.08",
+            "-p",
+            "description,temp,humidity,wind_speed",
+        ])
+        .await;
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "clear sky\n0.00°C\n40%\n0.00m/s\n");
+}
+
+#[tokio::test]
+async fn accepts_unit_system_names() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .with_config("units: imperial\n")
+        .run(&[
+            "-k", KEY, "--lat", "37.39", "--lon", "-122.08", "-p", "temp",
+        ])
+        .await;
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "48.92°F\n");
+
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k", KEY, "--lat", "37.39", "--lon", "-122.08", "--units", "standard", "-p", "temp",
+        ])
+        .await;
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "282.55°K\n");
+}
+
+#[tokio::test]
+async fn rejects_unknown_units() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k", KEY, "--lat", "37.39", "--lon", "-122.08", "--units", "X", "-p", "temp",
+        ])
+        .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout(&output).is_empty());
+    assert!(stderr(&output).contains("invalid value 'X' for '--units <UNITS>'"));
+}
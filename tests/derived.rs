@@ -0,0 +1,159 @@
+mod common;
+
+use common::run;
+use openweathercli::data::derived::{
+    absolute_humidity, dew_point, heat_index, humidex, wet_bulb, wind_chill,
+};
+
+fn fahrenheit(temp: f32) -> f32 {
+    temp * 9.0 / 5.0 + 32.0
+}
+
+fn celsius(temp: f32) -> f32 {
+    (temp - 32.0) * 5.0 / 9.0
+}
+
+fn assert_near(actual: f32, expected: f32, tolerance: f32) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "expected {} ± {}, got {}",
+        expected,
+        tolerance,
+        actual
+    );
+}
+
+#[test]
+fn dew_point_matches_reference() {
+    // (°C, %, dew point °C)
+    for (temp, humidity, expected) in [
+        (20.0, 50.0, 9.3),
+        (30.0, 70.0, 23.9),
+        (10.0, 80.0, 6.7),
+        (0.0, 100.0, 0.0),
+    ] {
+        assert_near(dew_point(temp, humidity).unwrap(), expected, 0.1);
+    }
+    assert_eq!(dew_point(20.0, 0.0), None);
+}
+
+#[test]
+fn heat_index_matches_nws_chart() {
+    // NWS heat index chart, in °F
+    for (temp, humidity, expected) in [
+        (80.0, 40.0, 80.0),
+        (90.0, 60.0, 100.0),
+        (96.0, 65.0, 121.0),
+        (100.0, 40.0, 109.0),
+        (86.0, 90.0, 105.0),
+        (110.0, 40.0, 136.0),
+    ] {
+        let index = fahrenheit(heat_index(celsius(temp), humidity));
+        assert_eq!(index.round(), expected, "{}°F at {}%", temp, humidity);
+    }
+}
+
+#[test]
+fn wind_chill_matches_environment_canada_table() {
+    // Environment Canada wind chill table, °C and km/h
+    for (temp, kmh, expected) in [
+        (0.0, 10.0, -3.0),
+        (-10.0, 20.0, -18.0),
+        (-20.0, 30.0, -33.0),
+        (-30.0, 50.0, -49.0),
+        (-5.0, 40.0, -14.0),
+    ] {
+        let index = wind_chill(temp, kmh / 3.6).unwrap();
+        assert_eq!(index.round(), expected, "{}°C at {}km/h", temp, kmh);
+    }
+
+    // Undefined above 10°C or in calm air
+    assert_eq!(wind_chill(15.0, 10.0), None);
+    assert_eq!(wind_chill(0.0, 1.0), None);
+}
+
+#[test]
+fn humidex_matches_environment_canada_table() {
+    // (°C, dew point °C, humidex)
+    for (temp, dew_point, expected) in [
+        (30.0, 15.0, 34.0),
+        (30.0, 25.0, 42.0),
+        (35.0, 20.0, 43.0),
+        (25.0, 20.0, 33.0),
+    ] {
+        assert_eq!(humidex(temp, dew_point).round(), expected);
+    }
+}
+
+#[test]
+fn absolute_humidity_matches_saturation_table() {
+    // Saturated air, g/m³
+    for (temp, expected) in [(0.0, 4.85), (20.0, 17.3), (30.0, 30.4)] {
+        assert_near(absolute_humidity(temp, 100.0), expected, 0.1);
+    }
+    assert_near(absolute_humidity(25.0, 50.0), 11.5, 0.1);
+}
+
+#[test]
+fn wet_bulb_matches_stull() {
+    // Stull (2011) worked example and psychrometric chart values
+    for (temp, humidity, expected) in [(20.0, 50.0, 13.7), (30.0, 80.0, 27.1), (10.0, 90.0, 8.9)] {
+        assert_near(wet_bulb(temp, humidity), expected, 0.1);
+    }
+}
+
+#[tokio::test]
+async fn prints_derived_options() {
+    assert_eq!(
+        run(
+            "/data/2.5/weather",
+            "current_weather.json",
+            &[
+                "-v",
+                "-p",
+                "dew_point,wind_chill,humidex,absolute_humidity,wet_bulb"
+            ]
+        )
+        .await,
+        "Dew Point: 9.40°C\n\
+         Wind Chill Index: 6.99°C\n\
+         Humidex: 10.41°C\n\
+         Absolute Humidity: 9.04g/m³\n\
+         Wet Bulb Temperature: 9.34°C\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_derived_options_in_display_units() {
+    assert_eq!(
+        run(
+            "/data/2.5/weather",
+            "current_weather.json",
+            &["--units", "I", "-p", "dew_point,wind_chill"]
+        )
+        .await,
+        "48.92°F\n44.58°F\n"
+    );
+}
+
+#[tokio::test]
+async fn aggregates_derived_options_per_day() {
+    assert_eq!(
+        run(
+            "/data/2.5/forecast",
+            "forecast.json",
+            &[
+                "--api",
+                "forecast",
+                "--daily",
+                "-p",
+                "heat_index,wind_chill"
+            ]
+        )
+        .await,
+        "[2022-08-30] 21.56°C\n\
+         [2022-08-31] 18.39°C\n\
+         [2022-08-30] No data to print for option wind_chill\n\
+         [2022-08-31] No data to print for option wind_chill\n"
+    );
+}
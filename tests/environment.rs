@@ -0,0 +1,160 @@
+mod common;
+
+use common::{serve, stderr, stdout, Owcli};
+
+const CONFIG: &str = "\
+key: test-key
+lat: 52.52
+lon: 13.405
+units: imperial
+locations:
+  home:
+    lat: 37.39
+    lon: -122.08
+";
+
+#[tokio::test]
+async fn reads_the_key_from_the_environment() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .with_env("OWM_API_KEY", "test-key")
+        .run(&["--lat", "37.39", "--lon", "-122.08", "-p", "temp"])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "9.40°C\n");
+}
+
+#[tokio::test]
+async fn owcli_key_takes_precedence_over_owm_api_key() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .with_env("OWM_API_KEY", "other-key")
+        .with_env("OWCLI_KEY", "test-key")
+        .run(&["--lat", "37.39", "--lon", "-122.08", "-p", "temp"])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[tokio::test]
+async fn sits_between_flags_and_the_config_file() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server)
+        .with_config(CONFIG)
+        .with_env("OWCLI_UNITS", "M");
+
+    let env = owcli.run(&["-p", "temp"]).await;
+    let flag = owcli.run(&["-p", "temp", "--units", "standard"]).await;
+
+    assert_eq!(stdout(&env), "9.40°C\n");
+    assert_eq!(stdout(&flag), "282.55°K\n");
+}
+
+#[tokio::test]
+async fn location_flags_take_precedence_over_the_environment() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server)
+        .with_config(CONFIG)
+        .with_env("OWCLI_LAT", "1.5")
+        .with_env("OWCLI_LON", "2.5");
+
+    let output = owcli.run(&["config", "show"]).await;
+    assert!(stdout(&output).contains("lat: 1.5\nlon: 2.5\n"));
+
+    let output = owcli.run(&["--location", "home", "config", "show"]).await;
+    assert!(stdout(&output).contains("lat: 37.39\nlon: -122.08\n"));
+}
+
+#[tokio::test]
+async fn rejects_invalid_values() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .with_config(CONFIG)
+        .with_env("OWCLI_UNITS", "metrik")
+        .run(&["-p", "temp"])
+        .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Invalid environment variable OWCLI_UNITS=metrik"));
+}
+
+#[tokio::test]
+async fn loads_a_dotenv_file() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(CONFIG);
+    owcli.write(".env", "OWCLI_UNITS=metric\nOWCLI_LANG=de\n");
+
+    let output = owcli.run(&["-p", "temp"]).await;
+    assert_eq!(stdout(&output), "9.40°C\n");
+
+    // Variables already set are not replaced
+    let output = owcli
+        .with_env("OWCLI_UNITS", "standard")
+        .run(&["config", "show"])
+        .await;
+    assert!(stdout(&output).contains("units: standard\n"));
+    assert!(stdout(&output).contains("lang: de\n"));
+}
+
+#[tokio::test]
+async fn shows_a_dotenv_file_as_the_origin() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server)
+        .with_config(CONFIG)
+        .with_env("OWCLI_TZ", "utc");
+    let path = owcli.write(".env", "OWCLI_LANG=de\nOWCLI_TZ=location\n");
+
+    let output = owcli.run(&["config", "show", "--origin"]).await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains(&format!("lang: de  # OWCLI_LANG in {}\n", path.display())));
+    assert!(stdout(&output).contains("tz: utc  # OWCLI_TZ\n"));
+}
+
+#[tokio::test]
+async fn shows_where_settings_come_from() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server)
+        .with_config(CONFIG)
+        .with_env("OWCLI_KEY", "0123456789abcdef")
+        .with_env("OWCLI_TZ", "utc");
+    let path = owcli.config_path();
+
+    let output = owcli
+        .run(&["--lang", "fr", "config", "show", "--origin"])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "key: ************cdef  # OWCLI_KEY\n\
+             units: imperial  # {path}\n\
+             lat: 52.52  # {path}\n\
+             lon: 13.405  # {path}\n\
+             lang: fr  # --lang\n\
+             tz: utc  # OWCLI_TZ\n\
+             icons: emoji  # default\n\
+             wind_dir_format: deg  # default\n\
+             base_url: {}  # OWCLI_BASE_URL\n\
+             locations:  # {path}\n  \
+               home:\n    \
+                 lat: 37.39\n    \
+                 lon: -122.08\n",
+            server.uri(),
+            path = path.display()
+        )
+    );
+}
+
+#[tokio::test]
+async fn shows_settings_from_saved_locations() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .with_config(CONFIG)
+        .run(&["--location", "home", "config", "show", "--origin"])
+        .await;
+
+    assert!(stdout(&output).contains("lat: 37.39  # location home\n"));
+}
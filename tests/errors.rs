@@ -0,0 +1,112 @@
+mod common;
+
+use common::{mount, stderr, stdout, Owcli, KEY};
+use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+const SECRET: &str = "0123456789abcdef0123456789abcdef";
+
+async fn fail_with(status: u16, fixture: &str) -> std::process::Output {
+    let server = MockServer::start().await;
+    mount(&server, "/data/2.5/weather", status, fixture).await;
+
+    Owcli::new(&server)
+        .run(&[
+            "-k", KEY, "--lat", "37.39", "--lon", "-122.08", "-p", "temp",
+        ])
+        .await
+}
+
+#[tokio::test]
+async fn reports_invalid_key() {
+    let output = fail_with(401, "error_401.json").await;
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).contains("The API key was rejected: Invalid API key."));
+}
+
+#[tokio::test]
+async fn reports_not_found() {
+    let output = fail_with(404, "error_404.json").await;
+
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stderr(&output).contains("Not found: city not found"));
+}
+
+#[tokio::test]
+async fn reports_rate_limit() {
+    let output = fail_with(429, "error_429.json").await;
+
+    assert_eq!(output.status.code(), Some(5));
+    assert!(stderr(&output).contains("Rate limit exceeded: Your account is temporary blocked"));
+}
+
+#[tokio::test]
+async fn reports_missing_key() {
+    let server = MockServer::start().await;
+    let output = Owcli::new(&server)
+        .run(&["--lat", "37.39", "--lon", "-122.08", "-p", "temp"])
+        .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("No API key found!"));
+}
+
+#[tokio::test]
+async fn reports_unknown_api() {
+    let server = MockServer::start().await;
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k", KEY, "--lat", "37.39", "--lon", "-122.08", "--api", "hourly",
+        ])
+        .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Unknown API: hourly"));
+}
+
+#[tokio::test]
+async fn hides_the_key_from_network_errors() {
+    let server = MockServer::start().await;
+    // A port that was just free, so nothing listens on it
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let base_url = format!("http://127.0.0.1:{}", port);
+    let owcli = Owcli::new(&server).with_env("OWCLI_BASE_URL", &base_url);
+
+    for args in [
+        &["--lat", "37.39", "--lon", "-122.08", "-p", "temp"][..],
+        &["--city", "Berlin", "-p", "temp"],
+        &["--api", "forecast", "--lat", "37.39", "--lon", "-122.08"],
+    ] {
+        let output = owcli.run(&[&["-k", SECRET], args].concat()).await;
+
+        assert_eq!(output.status.code(), Some(6), "{}", stderr(&output));
+        assert!(stderr(&output).contains("Network error"));
+        assert!(stderr(&output).contains("appid=***"), "{}", stderr(&output));
+        assert!(!stderr(&output).contains(SECRET), "{}", stderr(&output));
+    }
+}
+
+#[tokio::test]
+async fn hides_the_key_from_api_messages() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(401).set_body_string(format!(
+            "{{\"cod\": 401, \"message\": \"Invalid API key {}\"}}",
+            SECRET
+        )))
+        .mount(&server)
+        .await;
+
+    let output = Owcli::new(&server)
+        .run(&["-k", SECRET, "--lat", "37.39", "--lon", "-122.08"])
+        .await;
+
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).contains("Invalid API key ***"));
+    assert!(!stderr(&output).contains(SECRET));
+}
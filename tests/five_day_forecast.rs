@@ -0,0 +1,146 @@
+mod common;
+
+use common::{fixture, serve, stderr, stdout, Owcli, KEY};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+async fn forecast(args: &[&str]) -> String {
+    let server = serve("/data/2.5/forecast", "forecast.json").await;
+    let base = [
+        "-k", KEY, "--lat", "44.34", "--lon", "10.99", "--api", "forecast",
+    ];
+    let output = Owcli::new(&server).run(&[&base[..], args].concat()).await;
+
+    assert!(output.status.success());
+    stdout(&output)
+}
+
+#[tokio::test]
+async fn prints_slots_in_metric() {
+    assert_eq!(
+        forecast(&["-p", "temp"]).await,
+        "[2022-08-30 11:00:00] 17.00°C\n\
+         [2022-08-30 14:00:00] 22.00°C\n\
+         [2022-08-31 11:00:00] 15.00°C\n\
+         [2022-08-31 14:00:00] 19.00°C\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_slots_in_imperial() {
+    assert_eq!(
+        forecast(&["--units", "I", "-p", "temp,wind_speed,rain_3h"]).await,
+        "[2022-08-30 11:00:00] 62.60°F\n\
+         [2022-08-30 14:00:00] 71.60°F\n\
+         [2022-08-31 11:00:00] 59.00°F\n\
+         [2022-08-31 14:00:00] 66.20°F\n\
+         [2022-08-30 11:00:00] 4.47mph\n\
+         [2022-08-30 14:00:00] 11.18mph\n\
+         [2022-08-31 11:00:00] 6.71mph\n\
+         [2022-08-31 14:00:00] 8.95mph\n\
+         [2022-08-30 11:00:00] 0.06in\n\
+         [2022-08-30 14:00:00] 0.10in\n\
+         [2022-08-31 11:00:00] 0.00in\n\
+         [2022-08-31 14:00:00] 0.00in\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_slots_in_standard() {
+    assert_eq!(
+        forecast(&["--units", "S", "-p", "feels_like"]).await,
+        "[2022-08-30 11:00:00] 289.65°K\n\
+         [2022-08-30 14:00:00] 295.15°K\n\
+         [2022-08-31 11:00:00] 287.15°K\n\
+         [2022-08-31 14:00:00] 291.65°K\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_verbose_slots() {
+    assert_eq!(
+        forecast(&["-v", "-p", "pop,description"]).await,
+        "[2022-08-30 11:00:00] Chance of Precipitation: 40%\n\
+         [2022-08-30 14:00:00] Chance of Precipitation: 70%\n\
+         [2022-08-31 11:00:00] Chance of Precipitation: 0%\n\
+         [2022-08-31 14:00:00] Chance of Precipitation: 10%\n\
+         [2022-08-30 11:00:00] Weather description: light rain\n\
+         [2022-08-30 14:00:00] Weather description: light rain\n\
+         [2022-08-31 11:00:00] Weather description: broken clouds\n\
+         [2022-08-31 14:00:00] Weather description: clear sky\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_daily_aggregates() {
+    assert_eq!(
+        forecast(&[
+            "--daily",
+            "-p",
+            "temp,pop,rain_3h,wind_speed,wind_dir,description"
+        ])
+        .await,
+        "[2022-08-30] 24.00°C, 16.00°C\n\
+         [2022-08-31] 20.00°C, 14.00°C\n\
+         [2022-08-30] 70%\n\
+         [2022-08-31] 10%\n\
+         [2022-08-30] 4.00mm\n\
+         [2022-08-31] 0.00mm\n\
+         [2022-08-30] 5.00m/s\n\
+         [2022-08-31] 4.00m/s\n\
+         [2022-08-30] 0°\n\
+         [2022-08-31] 190°\n\
+         [2022-08-30] light rain\n\
+         [2022-08-31] broken clouds\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_verbose_daily_aggregates() {
+    assert_eq!(
+        forecast(&["--daily", "-v", "--units", "I", "-p", "temp,humidity"]).await,
+        "[2022-08-30] High: 75.20°F, Low: 60.80°F\n\
+         [2022-08-31] High: 68.00°F, Low: 57.20°F\n\
+         [2022-08-30] Humidity: 55%\n\
+         [2022-08-31] Humidity: 62%\n"
+    );
+}
+
+#[tokio::test]
+async fn leaves_out_slots_without_a_time() {
+    let mut forecast: serde_json::Value = serde_json::from_str(&fixture("forecast.json")).unwrap();
+    let slots = forecast["list"].as_array_mut().unwrap();
+    let mut undated = slots[0].clone();
+    undated.as_object_mut().unwrap().remove("dt");
+    undated.as_object_mut().unwrap().remove("dt_txt");
+    undated["main"]["temp_max"] = 40.into();
+    slots.push(undated);
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/data/2.5/forecast"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(forecast))
+        .mount(&server)
+        .await;
+    let owcli = Owcli::new(&server);
+    let base = [
+        "-k", KEY, "--lat", "44.34", "--lon", "10.99", "--api", "forecast",
+    ];
+
+    let daily = owcli
+        .run(&[&base[..], &["--daily", "-p", "temp"]].concat())
+        .await;
+    assert!(daily.status.success(), "{}", stderr(&daily));
+    assert_eq!(
+        stdout(&daily),
+        "[2022-08-30] 24.00°C, 16.00°C\n[2022-08-31] 20.00°C, 14.00°C\n"
+    );
+
+    let summary = owcli
+        .run(&[&base[..], &["--summary", "short"]].concat())
+        .await;
+    assert!(summary.status.success(), "{}", stderr(&summary));
+    assert_eq!(stdout(&summary).matches("°C").count(), 4);
+}
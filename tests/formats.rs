@@ -0,0 +1,104 @@
+mod common;
+
+use common::run;
+
+async fn current(args: &[&str]) -> String {
+    run("/data/2.5/weather", "current_weather.json", args).await
+}
+
+async fn forecast(args: &[&str]) -> String {
+    let args = [&["--api", "forecast"], args].concat();
+    run("/data/2.5/forecast", "forecast.json", &args).await
+}
+
+#[tokio::test]
+async fn renders_json_object() {
+    assert_eq!(
+        current(&["--format", "json", "-p", "temp,pressure,description,snow_1h"]).await,
+        "{\n  \"temp\": 9.4,\n  \"pressure\": 1023,\n  \"description\": \"light rain\",\n  \"snow_1h\": null\n}\n"
+    );
+}
+
+#[tokio::test]
+async fn renders_whole_record_without_print() {
+    let output = current(&["--format", "json", "--units", "I"]).await;
+    let record: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(record["lat"], 37.3861);
+    assert_eq!(record["temp"], 48.92);
+    assert_eq!(record["wind_speed"], 10.29);
+    assert_eq!(record["rain_1h"], 0.1);
+    assert_eq!(record["clouds"], 90);
+    assert!(record["snow_3h"].is_null());
+    assert_eq!(record["glyph"], "🌧️");
+    assert_eq!(record["sunrise"], "05:47");
+    assert_eq!(record.as_object().unwrap().len(), 33);
+}
+
+#[tokio::test]
+async fn renders_yaml_document() {
+    assert_eq!(
+        current(&["--format", "yaml", "-p", "temp,humidity,weather"]).await,
+        "temp: 9.4\nhumidity: 100\nweather: Rain\n"
+    );
+}
+
+#[tokio::test]
+async fn renders_csv_with_header() {
+    assert_eq!(
+        current(&[
+            "--format",
+            "csv",
+            "--units",
+            "S",
+            "-p",
+            "temp,wind_speed,description"
+        ])
+        .await,
+        "temp,wind_speed,description\n282.55,4.60,light rain\n"
+    );
+}
+
+#[tokio::test]
+async fn renders_forecast_slots_as_tsv() {
+    assert_eq!(
+        forecast(&["--format", "tsv", "-p", "temp,pop"]).await,
+        "time\ttemp\tpop\n\
+         2022-08-30 11:00:00\t17.00\t40\n\
+         2022-08-30 14:00:00\t22.00\t70\n\
+         2022-08-31 11:00:00\t15.00\t0\n\
+         2022-08-31 14:00:00\t19.00\t10\n"
+    );
+}
+
+#[tokio::test]
+async fn renders_daily_ranges_as_columns() {
+    assert_eq!(
+        forecast(&["--daily", "--format", "csv", "-p", "temp,rain_3h"]).await,
+        "date,temp_high,temp_low,rain_3h\n\
+         2022-08-30,24.00,16.00,4.00\n\
+         2022-08-31,20.00,14.00,0.00\n"
+    );
+}
+
+#[tokio::test]
+async fn renders_forecast_json_array() {
+    let output = forecast(&["--daily", "--format", "json", "-p", "temp,description"]).await;
+    let records: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(
+        records,
+        serde_json::json!([
+            {
+                "date": "2022-08-30",
+                "temp": { "high": 24.0, "low": 16.0 },
+                "description": "light rain"
+            },
+            {
+                "date": "2022-08-31",
+                "temp": { "high": 20.0, "low": 14.0 },
+                "description": "broken clouds"
+            }
+        ])
+    );
+}
@@ -0,0 +1,331 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{fixture, mount, mount_weather_at, stderr, stdout, Owcli, KEY};
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+#[tokio::test]
+async fn resolves_city_with_direct_geocoding() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/geo/1.0/direct"))
+        .and(query_param("q", "Mountain View,CA,US"))
+        .and(query_param("limit", "5"))
+        .and(query_param("appid", KEY))
+        .respond_with(ResponseTemplate::new(200).set_body_string(fixture("geocoding_direct.json")))
+        .mount(&server)
+        .await;
+    mount_weather_at(&server, "37.3893889", "-122.0832101", Duration::ZERO).await;
+
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k",
+            KEY,
+            "--city",
+            "Mountain View",
+            "--state",
+            "CA",
+            "--country",
+            "US",
+            "-p",
+            "temp",
+        ])
+        .await;
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "9.40°C\n");
+}
+
+#[tokio::test]
+async fn resolves_zip_with_zip_geocoding() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/geo/1.0/zip"))
+        .and(query_param("zip", "94040,US"))
+        .and(query_param("appid", KEY))
+        .respond_with(ResponseTemplate::new(200).set_body_string(fixture("geocoding_zip.json")))
+        .mount(&server)
+        .await;
+    mount_weather_at(&server, "37.3855", "-122.088", Duration::ZERO).await;
+
+    let output = Owcli::new(&server)
+        .run(&["-k", KEY, "--zip", "94040", "--country", "US", "-p", "temp"])
+        .await;
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "9.40°C\n");
+}
+
+#[tokio::test]
+async fn falls_back_to_zip_when_city_is_unknown() {
+    let server = MockServer::start().await;
+    mount(
+        &server,
+        "/geo/1.0/direct",
+        200,
+        "geocoding_direct_empty.json",
+    )
+    .await;
+    mount(&server, "/geo/1.0/zip", 200, "geocoding_zip.json").await;
+    mount_weather_at(&server, "37.3855", "-122.088", Duration::ZERO).await;
+
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k",
+            KEY,
+            "--city",
+            "Nowhere",
+            "--state",
+            "CA",
+            "--country",
+            "US",
+            "--zip",
+            "94040",
+            "-p",
+            "temp",
+        ])
+        .await;
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "9.40°C\n");
+}
+
+#[tokio::test]
+async fn fails_when_city_is_unknown() {
+    let server = MockServer::start().await;
+    mount(
+        &server,
+        "/geo/1.0/direct",
+        200,
+        "geocoding_direct_empty.json",
+    )
+    .await;
+
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k",
+            KEY,
+            "--city",
+            "Nowhere",
+            "--state",
+            "CA",
+            "--country",
+            "US",
+            "-p",
+            "temp",
+        ])
+        .await;
+
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).contains("Could not resolve a location"));
+}
+
+async fn mount_reverse(server: &MockServer, limit: &str) {
+    Mock::given(method("GET"))
+        .and(path("/geo/1.0/reverse"))
+        .and(query_param("lat", "37.39"))
+        .and(query_param("lon", "-122.08"))
+        .and(query_param("limit", limit))
+        .and(query_param("appid", KEY))
+        .respond_with(ResponseTemplate::new(200).set_body_string(fixture("geocoding_reverse.json")))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+async fn reverse(args: &[&str]) -> String {
+    let server = MockServer::start().await;
+    mount_reverse(&server, "5").await;
+
+    let base = [
+        "-k", KEY, "--lat", "37.39", "--lon", "-122.08", "--api", "reverse",
+    ];
+    let output = Owcli::new(&server).run(&[&base[..], args].concat()).await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    stdout(&output)
+}
+
+#[tokio::test]
+async fn lists_nearby_places() {
+    assert_eq!(
+        reverse(&["-s"]).await,
+        "1. Mountain View, California, US (37.3894, -122.0832)\n\
+         2. Los Altos, California, US (37.3791, -122.1166)\n"
+    );
+    assert_eq!(
+        reverse(&["-p", "name,local_names"]).await,
+        "[1] Mountain View\n\
+         [2] Los Altos\n\
+         [1] en: Mountain View, ru: Маунтин-Вью, uk: Маунтін-В'ю\n\
+         [2] en: Los Altos\n"
+    );
+}
+
+#[tokio::test]
+async fn localizes_place_names() {
+    // Places without a name in the language keep the English one
+    assert_eq!(
+        reverse(&["--lang", "ru", "-p", "place,country", "--format", "csv"]).await,
+        "place,country\n\
+         \"Маунтин-Вью, California, US\",US\n\
+         \"Los Altos, California, US\",US\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_place_of_current_weather() {
+    let server = MockServer::start().await;
+    mount(&server, "/data/2.5/weather", 200, "current_weather.json").await;
+    mount_reverse(&server, "1").await;
+
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k",
+            KEY,
+            "--lat",
+            "37.39",
+            "--lon",
+            "-122.08",
+            "--lang",
+            "uk_UA",
+            "-p",
+            "place,temp",
+        ])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Маунтін-В'ю, California, US\n9.40°C\n");
+}
+
+#[tokio::test]
+async fn skips_reverse_geocoding_unless_place_is_printed() {
+    let server = MockServer::start().await;
+    mount(&server, "/data/2.5/weather", 200, "current_weather.json").await;
+
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k", KEY, "--lat", "37.39", "--lon", "-122.08", "-s", "short",
+        ])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+async fn mount_springfield(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/geo/1.0/direct"))
+        .and(query_param("q", "Springfield"))
+        .and(query_param("limit", "5"))
+        .and(query_param("appid", KEY))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(fixture("geocoding_direct_springfield.json")),
+        )
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn lists_candidates_when_city_is_ambiguous() {
+    let server = MockServer::start().await;
+    mount_springfield(&server).await;
+
+    let output = Owcli::new(&server)
+        .run(&["-k", KEY, "--city", "Springfield", "-p", "temp"])
+        .await;
+
+    assert_eq!(output.status.code(), Some(7));
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).contains(
+        "Several places match, pass --pick N to choose one:\n  \
+         1. Springfield, Illinois, US (39.7990, -89.6440)\n  \
+         2. Springfield, Missouri, US (37.2082, -93.2923)\n  \
+         3. Springfield, Massachusetts, US (42.1019, -72.5887)\n"
+    ));
+}
+
+#[tokio::test]
+async fn picks_a_candidate() {
+    let server = MockServer::start().await;
+    mount_springfield(&server).await;
+    mount_weather_at(&server, "37.2081729", "-93.2922715", Duration::ZERO).await;
+
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k",
+            KEY,
+            "--city",
+            "Springfield",
+            "--pick",
+            "2",
+            "-p",
+            "temp",
+        ])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "9.40°C\n");
+
+    let output = Owcli::new(&server)
+        .run(&[
+            "-k",
+            KEY,
+            "--city",
+            "Springfield",
+            "--pick",
+            "4",
+            "-p",
+            "temp",
+        ])
+        .await;
+
+    assert_eq!(output.status.code(), Some(7));
+}
+
+#[tokio::test]
+async fn saves_the_picked_place() {
+    let server = MockServer::start().await;
+    mount_springfield(&server).await;
+    mount_weather_at(&server, "39.7990175", "-89.6439575", Duration::ZERO).await;
+
+    let owcli = Owcli::new(&server).with_config("units: imperial\n");
+    let output = owcli
+        .run(&[
+            "-k",
+            KEY,
+            "--city",
+            "Springfield",
+            "--pick",
+            "1",
+            "--save-location",
+            "home",
+            "--units",
+            "metric",
+            "-p",
+            "temp",
+        ])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("Saved Springfield, Illinois, US (39.7990, -89.6440) as home"));
+
+    let config = std::fs::read_to_string(owcli.config_path()).unwrap();
+    assert_eq!(
+        config,
+        "units: imperial\n\
+         locations:\n  \
+         home:\n    \
+         lat: 39.7990175\n    \
+         lon: -89.6439575\n    \
+         name: Springfield\n    \
+         state: Illinois\n    \
+         country: US\n"
+    );
+}
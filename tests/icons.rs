@@ -0,0 +1,100 @@
+mod common;
+
+use common::{mount, run, stdout, Owcli, KEY};
+use wiremock::MockServer;
+
+async fn current(fixture: &str, args: &[&str]) -> String {
+    run("/data/2.5/weather", fixture, args).await
+}
+
+async fn forecast(args: &[&str]) -> String {
+    let args = [&["--api", "forecast"][..], args].concat();
+    run("/data/2.5/forecast", "forecast.json", &args).await
+}
+
+#[tokio::test]
+async fn prints_emoji_glyph_by_default() {
+    assert_eq!(
+        current("current_weather.json", &["-p", "glyph", "-v"]).await,
+        "Weather Glyph: 🌧️\n"
+    );
+}
+
+#[tokio::test]
+async fn uses_night_variant_from_icon() {
+    assert_eq!(
+        current("current_weather_minimal.json", &["-p", "glyph"]).await,
+        "🌙\n"
+    );
+    assert_eq!(
+        current(
+            "current_weather_minimal.json",
+            &["-p", "glyph", "--icons", "ascii"]
+        )
+        .await,
+        "clear night\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_nerd_font_glyph() {
+    assert_eq!(
+        current("current_weather.json", &["-p", "glyph", "--icons", "nerd"]).await,
+        "\u{e308}\n"
+    );
+}
+
+#[tokio::test]
+async fn reads_icons_from_config() {
+    let server = MockServer::start().await;
+    mount(&server, "/data/2.5/weather", 200, "current_weather.json").await;
+
+    let output = Owcli::new(&server)
+        .with_config("icons: ascii\n")
+        .run(&[
+            "-k", KEY, "--lat", "37.39", "--lon", "-122.08", "-p", "glyph",
+        ])
+        .await;
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "rain\n");
+}
+
+#[tokio::test]
+async fn prints_forecast_glyphs() {
+    assert_eq!(
+        forecast(&["-p", "glyph", "--icons", "ascii"]).await,
+        "[2022-08-30 11:00:00] rain\n\
+         [2022-08-30 14:00:00] rain\n\
+         [2022-08-31 11:00:00] cloudy\n\
+         [2022-08-31 14:00:00] clear\n"
+    );
+    assert_eq!(
+        forecast(&["-p", "glyph", "--daily", "--icons", "ascii"]).await,
+        "[2022-08-30] rain\n[2022-08-31] cloudy\n"
+    );
+}
+
+#[tokio::test]
+async fn prefixes_summary_when_icons_are_set() {
+    assert_eq!(
+        current("current_weather.json", &["-s", "short", "--icons", "emoji"]).await,
+        "Mountain View, US: 🌧️ Light rain, 9.40°C (feels like 8.71°C)\n"
+    );
+    assert_eq!(
+        current("current_weather.json", &["-s", "short", "--icons", "none"]).await,
+        "Mountain View, US: Light rain, 9.40°C (feels like 8.71°C)\n"
+    );
+}
+
+#[tokio::test]
+async fn renders_glyph_in_template() {
+    assert_eq!(
+        current(
+            "current_weather.json",
+            &["-t", "{glyph} {temp:.0}{unit_temp}", "--icons", "ascii"]
+        )
+        .await,
+        "rain 9°C\n"
+    );
+}
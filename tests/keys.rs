@@ -0,0 +1,135 @@
+mod common;
+
+use common::{serve, stderr, stdout, Owcli};
+
+fn config(source: &str) -> String {
+    format!("lat: 37.39\nlon: -122.08\n{}\n", source)
+}
+
+#[cfg(unix)]
+fn chmod(path: &std::path::Path, mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).unwrap();
+}
+
+#[tokio::test]
+async fn reads_the_key_from_a_command() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .with_config(&config("key_command: echo test-key; echo notes"))
+        .run(&["-p", "temp"])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "9.40°C\n");
+}
+
+#[tokio::test]
+async fn reports_a_failing_command() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    for (command, error) in [
+        ("exit 3", "key_command `exit 3` failed with exit status: 3"),
+        ("true", "key_command `true` printed no key"),
+    ] {
+        let output = Owcli::new(&server)
+            .with_config(&config(&format!("key_command: {}", command)))
+            .run(&["-p", "temp"])
+            .await;
+
+        assert_eq!(output.status.code(), Some(2));
+        assert!(
+            stderr(&output).contains(&format!("Could not get the API key: {}", error)),
+            "{}",
+            stderr(&output)
+        );
+    }
+}
+
+#[tokio::test]
+async fn only_runs_the_command_when_needed() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(&config("key_command: exit 1"));
+
+    let flag = owcli.run(&["-k", "test-key", "-p", "temp"]).await;
+    let env = owcli
+        .with_env("OWM_API_KEY", "test-key")
+        .run(&["-p", "temp"])
+        .await;
+
+    assert!(flag.status.success(), "{}", stderr(&flag));
+    assert!(env.status.success(), "{}", stderr(&env));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn reads_the_key_from_a_private_file() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(&config("key_file: ~/owm.key"));
+    let path = owcli.write("owm.key", "test-key\n");
+
+    chmod(&path, 0o600);
+    let output = owcli.run(&["-p", "temp"]).await;
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "9.40°C\n");
+
+    chmod(&path, 0o644);
+    let output = owcli.run(&["-p", "temp"]).await;
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains(&format!(
+        "key_file {} can be read by other users, run `chmod 600 {}`",
+        path.display(),
+        path.display()
+    )));
+}
+
+#[tokio::test]
+async fn reports_a_missing_file() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .with_config(&config("key_file: /nonexistent/owm.key"))
+        .run(&["-p", "temp"])
+        .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Could not get the API key: key_file /nonexistent/owm.key"));
+}
+
+#[tokio::test]
+async fn shows_where_the_key_comes_from() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(&config("key_command: echo 0123456789abcdef"));
+
+    let output = owcli.run(&["config", "show", "--origin"]).await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).starts_with(&format!(
+        "key: ************cdef  # key_command in {path}\n\
+         key_command: echo 0123456789abcdef  # {path}\n",
+        path = owcli.config_path().display()
+    )));
+}
+
+#[tokio::test]
+async fn init_does_not_copy_the_key_into_the_file() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config(&config("key_command: echo test-key"));
+
+    let output = owcli.run_with_input(&["config", "init"], "\n\n\n").await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!std::fs::read_to_string(owcli.config_path())
+        .unwrap()
+        .contains("key:"));
+}
+
+#[cfg(not(feature = "keyring"))]
+#[tokio::test]
+async fn set_key_needs_keyring_support() {
+    let server = serve("/data/2.5/weather", "current_weather.json").await;
+    let output = Owcli::new(&server)
+        .run_with_input(&["config", "set-key"], "test-key\n")
+        .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("this build has no keyring support"));
+}
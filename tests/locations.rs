@@ -0,0 +1,234 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{mount, mount_weather_at, stderr, stdout, Owcli};
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const CONFIG: &str = "\
+key: test-key
+default_location: home
+locations:
+  home:
+    lat: 37.39
+    lon: -122.08
+  berlin:
+    lat: 52.52
+    lon: 13.405
+    units: imperial
+    lang: ru
+  springfield:
+    city: Springfield
+    state: IL
+    country: US
+";
+
+async fn temp(args: &[&str]) -> std::process::Output {
+    let server = MockServer::start().await;
+    mount_weather_at(&server, "37.39", "-122.08", Duration::ZERO).await;
+    mount_weather_at(&server, "52.52", "13.405", Duration::ZERO).await;
+    mount_weather_at(&server, "40.5", "-90.5", Duration::ZERO).await;
+
+    Owcli::new(&server)
+        .with_config(CONFIG)
+        .run(
+            &[
+                args,
+                &["-p", "temp,wind_dir", "--wind-dir-format", "compass16"],
+            ]
+            .concat(),
+        )
+        .await
+}
+
+#[tokio::test]
+async fn uses_the_default_location() {
+    let output = temp(&[]).await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "9.40°C\nN\n");
+}
+
+#[tokio::test]
+async fn selects_a_location_with_its_units_and_lang() {
+    let output = temp(&["--location", "berlin"]).await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "48.92°F\nС\n");
+}
+
+#[tokio::test]
+async fn command_line_location_takes_precedence_over_the_default() {
+    let output = temp(&["--lat", "40.5", "--lon", "-90.5"]).await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "9.40°C\nN\n");
+
+    let output = temp(&["--location", "home", "--lat", "40.5"]).await;
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[tokio::test]
+async fn geocodes_locations_given_by_name() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/geo/1.0/direct"))
+        .and(query_param("q", "Springfield,IL,US"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(common::fixture("geocoding_direct_springfield.json")),
+        )
+        .mount(&server)
+        .await;
+    mount_weather_at(&server, "39.7990175", "-89.6439575", Duration::ZERO).await;
+
+    let output = Owcli::new(&server)
+        .with_config(CONFIG)
+        .run(&["--location", "springfield", "--pick", "1", "-p", "temp"])
+        .await;
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "9.40°C\n");
+}
+
+#[tokio::test]
+async fn rejects_unknown_locations() {
+    let output = temp(&["--location", "mars"]).await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("No location named mars in the configuration file"));
+}
+
+#[tokio::test]
+async fn adds_lists_and_removes_locations() {
+    let server = MockServer::start().await;
+    mount(&server, "/data/2.5/weather", 200, "current_weather.json").await;
+    let owcli = Owcli::new(&server).with_config("# Office sites\nkey: test-key\n");
+
+    let output = owcli
+        .run(&[
+            "location",
+            "add",
+            "office-berlin",
+            "--lat",
+            "52.52",
+            "--lon",
+            "13.405",
+            "--units",
+            "metric",
+            "--default",
+        ])
+        .await;
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).starts_with("Saved 52.5200, 13.4050 as office-berlin in "));
+
+    let output = owcli
+        .run(&[
+            "location",
+            "add",
+            "home",
+            "--zip",
+            "94040",
+            "--country",
+            "US",
+        ])
+        .await;
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = owcli.run(&["location", "list"]).await;
+    assert_eq!(
+        stdout(&output),
+        "home: 94040, US\noffice-berlin: 52.5200, 13.4050 (default)\n"
+    );
+
+    assert_eq!(
+        std::fs::read_to_string(owcli.config_path()).unwrap(),
+        "key: test-key\n\
+         locations:\n  \
+         office-berlin:\n    \
+         lat: 52.52\n    \
+         lon: 13.405\n    \
+         units: metric\n  \
+         home:\n    \
+         country: US\n    \
+         zip: '94040'\n\
+         default_location: office-berlin\n"
+    );
+
+    let output = owcli.run(&["location", "remove", "office-berlin"]).await;
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = owcli.run(&["location", "list"]).await;
+    assert_eq!(stdout(&output), "home: 94040, US\n");
+
+    let output = owcli.run(&["location", "remove", "office-berlin"]).await;
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[tokio::test]
+async fn rejects_locations_without_a_place() {
+    let server = MockServer::start().await;
+    let owcli = Owcli::new(&server).with_config("key: test-key\n");
+
+    let output = owcli
+        .run(&["location", "add", "nowhere", "--lat", "52.52"])
+        .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("nowhere needs lat and lon, a city, or a zip and country"));
+    assert_eq!(
+        std::fs::read_to_string(owcli.config_path()).unwrap(),
+        "key: test-key\n"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn keeps_the_permissions_of_the_config_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let server = MockServer::start().await;
+    let owcli = Owcli::new(&server).with_config("key: test-key\n");
+    let permissions = std::fs::Permissions::from_mode(0o600);
+    std::fs::set_permissions(owcli.config_path(), permissions).unwrap();
+
+    let output = owcli
+        .run(&["location", "add", "home", "--city", "Berlin"])
+        .await;
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let mode = std::fs::metadata(owcli.config_path())
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[tokio::test]
+async fn only_removes_locations_from_the_edited_file() {
+    let server = MockServer::start().await;
+    let owcli = Owcli::new(&server);
+    let project = owcli.write(
+        "project/.owcli.yaml",
+        "locations:\n  office:\n    lat: 52.52\n    lon: 13.405\n",
+    );
+
+    let output = owcli.run(&["location", "remove", "office"]).await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        stderr(&output).contains(&format!(
+            "office is defined in {}, remove it there",
+            project.display()
+        )),
+        "{}",
+        stderr(&output)
+    );
+    assert!(!owcli.config_path().exists());
+
+    let output = owcli.run(&["location", "list"]).await;
+    assert_eq!(stdout(&output), "office: 52.5200, 13.4050\n");
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::run;
+
+#[tokio::test]
+async fn prints_full_current_summary() {
+    assert_eq!(
+        run("/data/2.5/weather", "current_weather.json", &["--summary"]).await,
+        "Mountain View, US\n\
+         Light rain\n\
+         Temperature: 9.40°C, feels like 8.71°C\n\
+         Min/Max: 7.22°C / 11.11°C\n\
+         Humidity: 100%\n\
+         Pressure: 1023hPa\n\
+         Wind: 4.60m/s from 350°, gusts 7.20m/s\n\
+         Clouds: 90%\n\
+         Visibility: 10000m\n\
+         Rain: 2.54mm in the last hour\n\
+         Sunrise: 05:47\n\
+         Sunset: 20:29\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_short_current_summary() {
+    assert_eq!(
+        run(
+            "/data/2.5/weather",
+            "current_weather.json",
+            &["--units", "I", "-s", "short"]
+        )
+        .await,
+        "Mountain View, US: Light rain, 48.92°F (feels like 47.68°F)\n"
+    );
+}
+
+#[tokio::test]
+async fn leaves_out_missing_data() {
+    assert_eq!(
+        run(
+            "/data/2.5/weather",
+            "current_weather_minimal.json",
+            &["--summary"]
+        )
+        .await,
+        "Clear sky\nTemperature: 0.00°C\nHumidity: 40%\nWind: 0.00m/s\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_forecast_summary_per_day() {
+    assert_eq!(
+        run(
+            "/data/2.5/forecast",
+            "forecast.json",
+            &["--api", "forecast", "--summary", "full"]
+        )
+        .await,
+        "Zocca, IT\n\
+         [2022-08-30] Light rain\n  \
+         High/Low: 24.00°C / 16.00°C\n  \
+         Humidity: 55%\n  \
+         Wind: up to 5.00m/s, gusts 8.00m/s\n  \
+         Precipitation: 70% chance, 4.00mm rain\n\
+         [2022-08-31] Broken clouds\n  \
+         High/Low: 20.00°C / 14.00°C\n  \
+         Humidity: 62%\n  \
+         Wind: up to 4.00m/s, gusts 6.00m/s\n  \
+         Precipitation: 10% chance\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_short_forecast_summary() {
+    assert_eq!(
+        run(
+            "/data/2.5/forecast",
+            "forecast.json",
+            &["--api", "forecast", "-s", "short"]
+        )
+        .await,
+        "Zocca, IT\n\
+         [2022-08-30] Light rain, 24.00°C / 16.00°C, 70% chance of precipitation\n\
+         [2022-08-31] Broken clouds, 20.00°C / 14.00°C, 10% chance of precipitation\n"
+    );
+}
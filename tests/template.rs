@@ -0,0 +1,176 @@
+mod common;
+
+use common::{mount, stderr, stdout, Owcli, KEY};
+use openweathercli::data::{
+    record::{Field, Record, Value},
+    template::Template,
+};
+use wiremock::MockServer;
+
+fn record() -> Record {
+    Record {
+        fields: vec![
+            Field::new("temp", "Temperature", Value::number("-3.46", "°C")),
+            Field::new("description", "Description", Value::text("light rain")),
+            Field::new(
+                "range",
+                "Range",
+                Value::range(Value::number("24.00", "°C"), Value::number("16.00", "°C")),
+            ),
+            Field::new("gust", "Wind Gust", Value::Null),
+        ],
+    }
+}
+
+fn render(template: &str) -> String {
+    Template::parse(template)
+        .unwrap()
+        .render(&record())
+        .unwrap()
+}
+
+#[test]
+fn interpolates_values_and_units() {
+    assert_eq!(
+        render("{temp}{unit_temp} {description}"),
+        "-3.46°C light rain"
+    );
+    assert_eq!(
+        render("{range_high:.0}/{range_low:.0}{unit_range}"),
+        "24/16°C"
+    );
+    assert_eq!(render("gusts: [{gust}]"), "gusts: []");
+}
+
+#[test]
+fn applies_precision_and_padding() {
+    assert_eq!(render("{temp:.1}"), "-3.5");
+    assert_eq!(render("[{temp:8.1}]"), "[    -3.5]");
+    assert_eq!(render("[{temp:<8}]"), "[-3.46   ]");
+    assert_eq!(render("[{temp:07.2}]"), "[-003.46]");
+    assert_eq!(render("[{description:12}]"), "[light rain  ]");
+    assert_eq!(render("[{description:*^14}]"), "[**light rain**]");
+    assert_eq!(render("[{description:.5}]"), "[light]");
+}
+
+#[test]
+fn escapes_braces() {
+    assert_eq!(render("{{temp}} = {temp}"), "{temp} = -3.46");
+}
+
+#[test]
+fn rejects_malformed_templates() {
+    for template in ["{temp", "temp}", "{}", "{temp:x}", "{temp:.}"] {
+        assert!(Template::parse(template).is_err(), "{}", template);
+    }
+}
+
+#[test]
+fn rejects_unknown_placeholders() {
+    let error = Template::parse("{tmp}")
+        .unwrap()
+        .render(&record())
+        .unwrap_err()
+        .to_string();
+
+    assert!(error.contains("unknown placeholder `{tmp}`"));
+    assert!(error.contains("temp, description, range, gust"));
+}
+
+async fn run(
+    route: &str,
+    fixture: &str,
+    owcli: fn(&MockServer) -> Owcli,
+    args: &[&str],
+) -> std::process::Output {
+    let server = MockServer::start().await;
+    mount(&server, route, 200, fixture).await;
+
+    let base = ["-k", KEY, "--lat", "37.39", "--lon", "-122.08"];
+    owcli(&server).run(&[&base[..], args].concat()).await
+}
+
+#[tokio::test]
+async fn renders_current_weather_template() {
+    let output = run(
+        "/data/2.5/weather",
+        "current_weather.json",
+        Owcli::new,
+        &[
+            "--template",
+            "{name}: {temp:.0}{unit_temp} {description} {wind_dir_compass}",
+        ],
+    )
+    .await;
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "Mountain View: 9°C light rain N\n");
+}
+
+#[tokio::test]
+async fn renders_named_template_from_config() {
+    let output = run(
+        "/data/2.5/weather",
+        "current_weather.json",
+        |server| {
+            Owcli::new(server).with_config(
+                "templates:\n  bar: \"{place} {temp:.1}{unit_temp} {humidity}{unit_humidity}\"\n",
+            )
+        },
+        &["--units", "I", "-t", "bar"],
+    )
+    .await;
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "Mountain View, US 48.9°F 100%\n");
+}
+
+#[tokio::test]
+async fn renders_one_line_per_forecast_day() {
+    let output = run(
+        "/data/2.5/forecast",
+        "forecast.json",
+        Owcli::new,
+        &[
+            "--api",
+            "forecast",
+            "--daily",
+            "-t",
+            "{date} {temp_high:.0}/{temp_low:.0}{unit_temp} {description}",
+        ],
+    )
+    .await;
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "2022-08-30 24/16°C light rain\n2022-08-31 20/14°C broken clouds\n"
+    );
+}
+
+#[tokio::test]
+async fn fails_on_malformed_template_before_fetching() {
+    let server = MockServer::start().await;
+    let output = Owcli::new(&server)
+        .run(&["-k", KEY, "--lat", "1", "--lon", "2", "-t", "{temp"])
+        .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("Invalid template"));
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn fails_on_unknown_placeholder() {
+    let output = run(
+        "/data/2.5/weather",
+        "current_weather.json",
+        Owcli::new,
+        &["-t", "{temperature}"],
+    )
+    .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).contains("unknown placeholder `{temperature}`"));
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{output, run, run_with_config, stderr, stdout};
+
+async fn current(config: &str, args: &[&str]) -> String {
+    run_with_config("/data/2.5/weather", "current_weather.json", config, args).await
+}
+
+async fn forecast(args: &[&str]) -> String {
+    let args = [&["--api", "forecast"][..], args].concat();
+    run("/data/2.5/forecast", "forecast.json", &args).await
+}
+
+#[tokio::test]
+async fn prints_times_at_location() {
+    assert_eq!(
+        current("", &["-v", "-p", "sunrise,sunset,day_length,observed_at"]).await,
+        "Sunrise: 05:47\n\
+         Sunset: 20:29\n\
+         Day Length: 14h 42m\n\
+         Observed At: 2019-06-12 07:44:05\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_times_in_utc() {
+    assert_eq!(
+        current("", &["--tz", "utc", "-p", "sunrise,sunset,observed_at"]).await,
+        "12:47\n03:29\n2019-06-12 14:44:05\n"
+    );
+}
+
+#[tokio::test]
+async fn reads_time_formats_from_config() {
+    let config = "time_format: \"%I:%M %p\"\ndatetime_format: \"%d/%m %H:%M\"\n";
+
+    assert_eq!(
+        current(config, &["-p", "sunrise,sunset,observed_at"]).await,
+        "05:47 AM\n08:29 PM\n12/06 07:44\n"
+    );
+}
+
+#[tokio::test]
+async fn prints_local_time() {
+    let output = current("", &["--datetime-format", "%Y", "-p", "local_time"]).await;
+
+    assert_eq!(output.trim().len(), 4);
+    assert!(output.trim().parse::<u32>().is_ok());
+}
+
+#[tokio::test]
+async fn prints_times_in_local_zone() {
+    // The local zone depends on the machine, so only the shape is checked
+    let output = current("", &["--tz", "local", "-p", "sunrise"]).await;
+    let (hours, minutes) = output.trim().split_once(':').unwrap();
+
+    assert!(hours.parse::<u8>().unwrap() < 24);
+    assert!(minutes.parse::<u8>().unwrap() < 60);
+}
+
+#[tokio::test]
+async fn rejects_invalid_time_format() {
+    let output = output(
+        "/data/2.5/weather",
+        "current_weather.json",
+        "",
+        &["--time-format", "%Q", "-p", "sunrise"],
+    )
+    .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout(&output).is_empty());
+    assert!(stderr(&output).contains("Invalid time format: %Q"));
+}
+
+#[tokio::test]
+async fn prints_forecast_slots_in_utc() {
+    assert_eq!(
+        forecast(&["--tz", "utc", "-p", "temp"]).await,
+        "[2022-08-30 09:00:00] 17.00°C\n\
+         [2022-08-30 12:00:00] 22.00°C\n\
+         [2022-08-31 09:00:00] 15.00°C\n\
+         [2022-08-31 12:00:00] 19.00°C\n"
+    );
+}
+
+#[tokio::test]
+async fn formats_forecast_slots() {
+    assert_eq!(
+        forecast(&["--datetime-format", "%a %H:%M", "-p", "description"]).await,
+        "[Tue 11:00] light rain\n\
+         [Tue 14:00] light rain\n\
+         [Wed 11:00] broken clouds\n\
+         [Wed 14:00] clear sky\n"
+    );
+}
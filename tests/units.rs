@@ -0,0 +1,147 @@
+mod common;
+
+use common::{output, run_with_config, stderr, stdout};
+
+async fn current(config: &str, args: &[&str]) -> String {
+    run_with_config("/data/2.5/weather", "current_weather.json", config, args).await
+}
+
+#[tokio::test]
+async fn converts_wind_speeds() {
+    assert_eq!(
+        current("", &["--wind-unit", "knots", "-p", "wind_speed,wind_gust"]).await,
+        "8.94kn\n14.00kn\n"
+    );
+    assert_eq!(
+        current("", &["--wind-unit", "km/h", "-p", "wind_speed"]).await,
+        "16.56km/h\n"
+    );
+    assert_eq!(
+        current("", &["--wind-unit", "bft", "-p", "wind_speed,wind_gust"]).await,
+        "3Bft\n4Bft\n"
+    );
+}
+
+#[tokio::test]
+async fn converts_pressure_distance_and_precipitation() {
+    assert_eq!(
+        current(
+            "",
+            &[
+                "--pressure-unit",
+                "inhg",
+                "--distance-unit",
+                "km",
+                "--precip-unit",
+                "in",
+                "-p",
+                "pressure,visibility,rain_1h",
+            ]
+        )
+        .await,
+        "30.21inHg\n10.0km\n0.10in\n"
+    );
+    assert_eq!(
+        current(
+            "",
+            &[
+                "--pressure-unit",
+                "mmhg",
+                "--distance-unit",
+                "miles",
+                "-p",
+                "pressure,visibility"
+            ]
+        )
+        .await,
+        "767mmHg\n6.2mi\n"
+    );
+}
+
+#[tokio::test]
+async fn overrides_imperial_system() {
+    assert_eq!(
+        current(
+            "",
+            &[
+                "--units",
+                "I",
+                "--temp-unit",
+                "c",
+                "--wind-unit",
+                "kn",
+                "-p",
+                "temp,wind_speed,rain_1h"
+            ]
+        )
+        .await,
+        "9.40°C\n8.94kn\n0.10in\n"
+    );
+}
+
+#[tokio::test]
+async fn reads_overrides_from_config() {
+    let config = "units:\n  system: imperial\n  wind: kmh\n  pressure: inHg\n";
+
+    assert_eq!(
+        current(config, &["-p", "temp,wind_speed,pressure"]).await,
+        "48.92°F\n16.56km/h\n30.21inHg\n"
+    );
+    // Flags take precedence over the config file
+    assert_eq!(
+        current(
+            config,
+            &[
+                "--units",
+                "M",
+                "--wind-unit",
+                "m/s",
+                "-p",
+                "temp,wind_speed"
+            ]
+        )
+        .await,
+        "9.40°C\n4.60m/s\n"
+    );
+}
+
+#[tokio::test]
+async fn converts_daily_forecast() {
+    let output = output(
+        "/data/2.5/forecast",
+        "forecast.json",
+        "",
+        &[
+            "--api",
+            "forecast",
+            "--daily",
+            "--wind-unit",
+            "knots",
+            "--pressure-unit",
+            "inhg",
+            "-p",
+            "wind_speed,pressure",
+        ],
+    )
+    .await;
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "[2022-08-30] 9.72kn\n[2022-08-31] 7.78kn\n[2022-08-30] 29.94inHg\n[2022-08-31] 30.09inHg\n"
+    );
+}
+
+#[tokio::test]
+async fn rejects_unknown_unit() {
+    let output = output(
+        "/data/2.5/weather",
+        "current_weather.json",
+        "",
+        &["--wind-unit", "furlongs", "-p", "wind_speed"],
+    )
+    .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("invalid value 'furlongs' for '--wind-unit <WIND_UNIT>'"));
+}
@@ -0,0 +1,189 @@
+mod common;
+
+use common::{run, run_with_config};
+use openweathercli::data::convert::{localize_compass, to_arrow, to_compass_points};
+
+#[test]
+fn compass_points_round_to_the_nearest() {
+    for (deg, eight, sixteen, thirty_two) in [
+        (0.0, "N", "N", "N"),
+        (12.0, "N", "NNE", "NbE"),
+        (22.5, "NE", "NNE", "NNE"),
+        (100.0, "E", "E", "EbS"),
+        (200.0, "S", "SSW", "SSW"),
+        (350.0, "N", "N", "NbW"),
+        (359.0, "N", "N", "N"),
+        (-90.0, "W", "W", "W"),
+    ] {
+        assert_eq!(to_compass_points(deg, 8), eight, "{}°", deg);
+        assert_eq!(to_compass_points(deg, 16), sixteen, "{}°", deg);
+        assert_eq!(to_compass_points(deg, 32), thirty_two, "{}°", deg);
+    }
+}
+
+#[test]
+fn arrows_point_downwind() {
+    // A northerly blows towards the south
+    for (deg, arrow) in [
+        (0.0, "↓"),
+        (45.0, "↙"),
+        (90.0, "←"),
+        (180.0, "↑"),
+        (270.0, "→"),
+        (350.0, "↓"),
+    ] {
+        assert_eq!(to_arrow(deg), arrow, "{}°", deg);
+    }
+}
+
+#[test]
+fn localizes_compass_points() {
+    assert_eq!(localize_compass("ENE", "de"), "ONO");
+    assert_eq!(localize_compass("WSW", "fr"), "OSO");
+    assert_eq!(localize_compass("SSE", "nl"), "ZZO");
+    assert_eq!(localize_compass("NW", "ru"), "СЗ");
+    assert_eq!(localize_compass("NbE", "de_DE"), "NzO");
+    assert_eq!(localize_compass("ENE", "ja"), "ENE");
+}
+
+#[test]
+fn localizes_points_in_between() {
+    for (point, lang, expected) in [
+        ("SWbW", "de", "SWzW"),
+        ("NbE", "ru", "СтВ"),
+        ("NEbN", "ru", "СВтС"),
+        ("NbE", "es", "N¼NE"),
+        ("NEbN", "es", "NE¼N"),
+        ("EbS", "es", "E¼SE"),
+        ("WbN", "es", "O¼NO"),
+        ("SSW", "es", "SSO"),
+        ("WbS", "fr", "O¼SO"),
+        ("NbE", "uk", "Пн¼ПнСх"),
+        ("SW", "uk", "ПдЗх"),
+    ] {
+        assert_eq!(
+            localize_compass(point, lang),
+            expected,
+            "{} in {}",
+            point,
+            lang
+        );
+    }
+}
+
+async fn current(args: &[&str]) -> String {
+    run("/data/2.5/weather", "current_weather.json", args).await
+}
+
+#[tokio::test]
+async fn prints_wind_dir_in_each_format() {
+    // The fixture's wind comes from 350°
+    for (format, expected) in [
+        ("deg", "350°\n"),
+        ("compass8", "N\n"),
+        ("compass16", "N\n"),
+        ("compass32", "NbW\n"),
+        ("arrow", "↓\n"),
+        ("arrow-from", "↑\n"),
+    ] {
+        assert_eq!(
+            current(&["--wind-dir-format", format, "-p", "wind_dir"]).await,
+            expected,
+            "{}",
+            format
+        );
+    }
+}
+
+#[tokio::test]
+async fn structured_output_uses_wind_dir_format() {
+    assert_eq!(
+        current(&[
+            "--wind-dir-format",
+            "compass32",
+            "-p",
+            "wind_dir",
+            "--format",
+            "json"
+        ])
+        .await,
+        "{\n  \"wind_dir\": \"NbW\"\n}\n"
+    );
+}
+
+#[tokio::test]
+async fn reads_wind_dir_format_and_lang_from_config() {
+    let config = "wind_dir_format: compass32\nlang: de\n";
+
+    assert_eq!(
+        run_with_config(
+            "/data/2.5/weather",
+            "current_weather.json",
+            config,
+            &["-p", "wind_dir"]
+        )
+        .await,
+        "NzW\n"
+    );
+}
+
+#[tokio::test]
+async fn forecast_table_uses_wind_dir_format() {
+    assert_eq!(
+        run_with_config(
+            "/data/2.5/forecast",
+            "forecast.json",
+            "",
+            &[
+                "--api",
+                "forecast",
+                "--wind-dir-format",
+                "compass16",
+                "--lang",
+                "fr",
+                "-p",
+                "wind_dir",
+                "--format",
+                "csv"
+            ]
+        )
+        .await,
+        "time,wind_dir\n\
+         2022-08-30 11:00:00,N\n\
+         2022-08-30 14:00:00,N\n\
+         2022-08-31 11:00:00,S\n\
+         2022-08-31 14:00:00,SSO\n"
+    );
+}
+
+#[tokio::test]
+async fn templates_keep_the_compass_point() {
+    assert_eq!(
+        current(&[
+            "--wind-dir-format",
+            "arrow",
+            "-t",
+            "{wind_dir} {wind_dir_compass}"
+        ])
+        .await,
+        "↓ N\n"
+    );
+    assert_eq!(
+        run_with_config(
+            "/data/2.5/forecast",
+            "forecast.json",
+            "",
+            &[
+                "--api",
+                "forecast",
+                "--daily",
+                "--wind-dir-format",
+                "arrow",
+                "-t",
+                "{date} {wind_dir} {wind_dir_compass}"
+            ]
+        )
+        .await,
+        "2022-08-30 ↓ N\n2022-08-31 ↑ S\n"
+    );
+}